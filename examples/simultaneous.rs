@@ -0,0 +1,39 @@
+//! **simultaneous** -- example of a fast multi-sensor temperature reading
+//!
+//! Every DS18B20 (and family) takes up to 750 ms to convert a temperature.
+//! Reading `temperature` device by device pays that delay for each sensor.
+//!
+//! Instead **read_all_temperatures**:
+//! * starts all conversions at once (**simultaneous_temperature**)
+//! * waits once
+//! * reads the stored `latesttemp` of each sensor (no further conversion)
+//!   * sensors are found by family code, whatever the address format (`-f`)
+//!
+//! ## SYNTAX
+//! ```text
+//! cargo run --example simultaneous -- [OPTIONS]
+//! ```
+//! OPTIONS are the usual owrust library options (e.g. `-s IP:port`, `-F`)
+
+// owrust project
+// https://github.com/alfille/owrust
+//
+// This is a Rust version of my C owfs code for talking to 1-wire devices via owserver
+// Basically owserver can talk to the physical devices, and provides network access via my "owserver protocol"
+//
+// MIT Licence
+// {c} 2025 Paul H Alfille
+
+use owrust::parse_args::{OwLib, Parser};
+
+fn main() -> owrust::OwEResult<()> {
+    let mut owserver = owrust::new();
+    OwLib.command_line(&mut owserver)?;
+
+    // convert, wait and read all -- in the scale chosen on the command line
+    let scale = owserver.to_config().temperature;
+    for (device, value) in owserver.read_all_temperatures(scale)? {
+        println!("{} {}", device, value);
+    }
+    Ok(())
+}
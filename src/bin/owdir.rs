@@ -22,7 +22,13 @@
 //! * `-s IP:port` (default `localhost:4304`)
 //! * `--dir`      Add trailing **/** for directory elements
 //! * `--bare`     Suppress non-device entries
+//! * `--no-bus`   Ask owserver to leave out `bus.N` entries
+//! * `--dir-cache-ttl SECONDS` Reuse a directory listing fetched within SECONDS
 //! * `--prune`    Even more spare output suppressing convenience files like `id` and `crc`
+//! * `--null`     End each entry with a NUL byte instead of a newline (for `xargs -0`)
+//! * `--columns N` Arrange entries in N columns (like `ls`)
+//! * `--include PATTERN` Only show entries whose name matches (`*` and `?` wildcards)
+//! * `--exclude PATTERN` Hide entries whose name matches
 //! * -h           for full list of options
 //!
 //! ## PATH
@@ -98,6 +104,16 @@
 //! /10.67C6697351FF/temphigh
 //! /10.67C6697351FF/templow
 //! ```
+//! Read the root directory in 3 columns
+//! ```
+//! owdir -s localhost:4304 --columns 3 /
+//! ```
+//! ```text
+//! /10.67C6697351FF  /settings    /simultaneous
+//! /05.4AEC29CDBAAB  /system      /alarm
+//! /bus.0            /statistics
+//! /uncached         /structure
+//! ```
 //! ### {c} 2025 Paul H Alfille -- MIT Licence
 
 // owrust project
@@ -106,10 +122,11 @@
 // This is a Rust version of my C owfs code for talking to 1-wire devices via owserver
 // Basically owserver can talk to the physical devices, and provides network access via my "owserver protocol"
 
-use owrust::console::console_lines;
+use owrust::console::{console_lines, console_separated, exit_code};
 use owrust::parse_args::{OwDir, Parser};
+use std::process::ExitCode;
 
-fn main() {
+fn main() -> ExitCode {
     let mut owserver = owrust::new(); // create structure for owserver communication
     let prog = OwDir;
 
@@ -118,24 +135,89 @@ fn main() {
         Ok(paths) => {
             if paths.is_empty() {
                 // No path -- assume root
-                from_path(&mut owserver, "/".to_string());
+                exit_code([from_path(&mut owserver, "/".to_string())])
             } else {
                 // for each path in command line
-                for path in paths.into_iter() {
-                    from_path(&mut owserver, path);
-                }
+                exit_code(paths.into_iter().map(|path| from_path(&mut owserver, path)))
             }
         }
         Err(e) => {
             eprintln!("owdir trouble {}", e);
+            ExitCode::FAILURE
         }
     }
 }
 
 // print 1-wire directory contents
-fn from_path(owserver: &mut owrust::OwMessage, path: String) {
+fn from_path(owserver: &mut owrust::OwMessage, path: String) -> bool {
     match owserver.dirall(&path) {
-        Ok(files) => console_lines(files),
-        Err(e) => eprintln!("Trouble with path {} Error {}", path, e),
+        Ok(files) if owserver.columns() > 1 => console_lines(columns(&files, owserver.columns())),
+        Ok(files) => console_separated(files, owserver.separator()),
+        Err(e) => {
+            owserver.trouble(format!("Trouble with path {} Error {}", path, e));
+            return false;
+        }
+    }
+    true
+}
+
+// arrange entries in rows of n columns, filled down each column (like ls)
+// * column width fits the longest entry plus 2 spaces
+// * no trailing spaces
+fn columns(entries: &[String], n: usize) -> Vec<String> {
+    if entries.is_empty() || n == 0 {
+        return Vec::new();
+    }
+    let rows = entries.len().div_ceil(n);
+    let widths: Vec<usize> = entries
+        .chunks(rows)
+        .map(|col| col.iter().map(|e| e.len()).max().unwrap_or(0) + 2)
+        .collect();
+    (0..rows)
+        .map(|r| {
+            let mut line = String::new();
+            for (c, width) in widths.iter().enumerate() {
+                if let Some(entry) = entries.get(c * rows + r) {
+                    line.push_str(&format!("{:<width$}", entry, width = width));
+                }
+            }
+            line.trim_end().to_string()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_test() {
+        let entries: Vec<String> = [
+            "/10.67C6697351FF",
+            "/bus.0",
+            "/uncached",
+            "/settings",
+            "/system",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        assert_eq!(
+            columns(&entries, 2),
+            vec![
+                "/10.67C6697351FF  /settings",
+                "/bus.0            /system",
+                "/uncached",
+            ]
+        );
+        assert_eq!(
+            columns(&entries, 3),
+            vec![
+                "/10.67C6697351FF  /uncached  /system",
+                "/bus.0            /settings",
+            ]
+        );
+        assert_eq!(columns(&entries, 1).len(), 5);
+        assert!(columns(&[], 3).is_empty());
     }
 }
@@ -0,0 +1,97 @@
+//! **owformat** -- _Rust version_
+//!
+//! ## Write a 1-wire device address in another format
+//!
+//! **owformat** is a tool in the 1-wire file system **OWFS**
+//!
+//! This Rust version of **owformat** is part of **owrust** -- the _Rust language_ OWFS programs
+//! * **OWFS** [documentation](https://owfs.org) and [code](https://github.com/owfs/owfs)
+//! * **owrust** [repository](https://github.com/alfille/owrust)
+//!
+//! ## SYNTAX
+//! ```
+//! owformat [OPTIONS] ADDRESS
+//! ```
+//! ## PURPOSE
+//! Change the written form of a device address
+//! * e.g. between owserver paths and raw hardware tools
+//! * the check byte (crc8) is added or removed as needed
+//! * no owserver needed
+//!
+//! ## OPTIONS
+//! * `-f` `--format` fi | f.i | fic | fi.c | f.ic | f.i.c (default f.i)
+//! * -h           for full list of options
+//!
+//! ## ADDRESS
+//! * 1-wire device address in any format
+//! * a leading `/` is kept
+//! * More than one address can be given
+//!
+//! ## USAGE
+//! * `owformat` is a command line program
+//! * output to stdout
+//!   * one line per address
+//! * errors to stderr
+//! * exit code 0 if every address is valid, 1 otherwise
+//!
+//! ## EXAMPLE
+//! ```
+//! owformat -f fic /10.67C6697351FF
+//! ```
+//! ```text
+//! /1067C6697351FF8D
+//! ```
+//! {c} 2025 Paul H Alfille -- MIT Licence
+
+// owrust project
+// https://github.com/alfille/owrust
+//
+// This is a Rust version of my C owfs code for talking to 1-wire devices via owserver
+// Basically owserver can talk to the physical devices, and provides network access via my "owserver protocol"
+//
+// MIT Licence
+// {c} 2025 Paul H Alfille
+
+use owrust::console::{console_line, exit_code};
+use owrust::parse_args::{OwFormat, Parser};
+use owrust::rom_id::reformat_address;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let mut owserver = owrust::new(); // holds the chosen format
+    let prog = OwFormat;
+
+    // configure and get addresses
+    match prog.command_line(&mut owserver) {
+        Ok(addresses) => {
+            if addresses.is_empty() {
+                eprintln!("No 1-wire address given");
+                return ExitCode::FAILURE;
+            }
+            exit_code(
+                addresses
+                    .into_iter()
+                    .map(|address| from_address(&owserver, address)),
+            )
+        }
+        Err(e) => {
+            eprintln!("owformat trouble {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// print one address in the new format
+// * false if not a valid address
+fn from_address(owserver: &owrust::OwMessage, address: String) -> bool {
+    match reformat_address(&address, owserver.format().clone()) {
+        Ok(a) => {
+            console_line(a);
+            true
+        }
+        Err(e) => {
+            owserver.trouble(format!("Trouble with address {} Error {}", address, e));
+            false
+        }
+    }
+}
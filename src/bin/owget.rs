@@ -24,7 +24,13 @@
 //! * `-s IP:port` (default `localhost:4304`)
 //! * `--dir`      Add trailing **/** for directory elements
 //! * `--bare`     Suppress non-device entries
+//! * `--no-bus`   Ask owserver to leave out `bus.N` entries
+//! * `--null`     End each entry with a NUL byte instead of a newline
+//! * `--timestamp start each result with the time (`--epoch` for seconds since 1970)
 //! * `--hex       show the value in hexidecimal
+//! * `--raw       write the value bytes unchanged (no newline)
+//! * `--trim      remove the space padding around values
+//! * `--units     add the unit to temperatures and pressures
 //! * `--size n    return only n bytes
 //! * `--offset m  start return at byte m
 //! * -h           for full list of options
@@ -64,10 +70,13 @@
 // This is a Rust version of my C owfs code for talking to 1-wire devices via owserver
 // Basically owserver can talk to the physical devices, and provides network access via my "owserver protocol"
 
-use owrust::console::console_line;
+use owrust::console::{console_bytes, console_separated, exit_code};
+use owrust::message::GetResult;
 use owrust::parse_args::{OwGet, Parser};
+use std::process::ExitCode;
+use std::time::SystemTime;
 
-fn main() {
+fn main() -> ExitCode {
     let mut owserver = owrust::new(); // create structure for owserver communication
     let prog = OwGet;
 
@@ -76,35 +85,41 @@ fn main() {
         Ok(paths) => {
             if paths.is_empty() {
                 // No path -- assume root
-                from_path(&mut owserver, "/".to_string());
+                exit_code([from_path(&mut owserver, "/".to_string())])
             } else {
-                // for each pathon command line
-                for path in paths.into_iter() {
-                    from_path(&mut owserver, path);
-                }
+                // for each path on command line
+                exit_code(paths.into_iter().map(|path| from_path(&mut owserver, path)))
             }
         }
         Err(e) => {
-            eprintln!("owread trouble {}", e);
+            eprintln!("owget trouble {}", e);
+            ExitCode::FAILURE
         }
     }
 }
 
 // print 1-wire file contents (e.g. a sensor reading)
-fn from_path(owserver: &mut owrust::OwMessage, path: String) {
+fn from_path(owserver: &mut owrust::OwMessage, path: String) -> bool {
     match owserver.get(&path) {
-        Ok(value) => {
+        Ok(GetResult::Dir(entries)) => console_separated(entries, owserver.separator()),
+        Ok(GetResult::Value(value)) if owserver.raw() => console_bytes(&value),
+        Ok(GetResult::Value(value)) => {
             match String::from_utf8(value) {
                 Ok(v) => {
-                    console_line(v);
+                    let stamp = owserver.stamp(SystemTime::now());
+                    let v = owserver.show_units(&path, v);
+                    console_separated([stamp + &v], owserver.separator());
                 }
                 Err(e) => {
-                    eprintln!("Unprintable string {}", e);
+                    owserver.trouble(format!("Unprintable string {}", e));
+                    return false;
                 }
             };
         }
         Err(e) => {
-            eprintln!("Trouble with path {} Error {}", path, e);
+            owserver.trouble(format!("Trouble with path {} Error {}", path, e));
+            return false;
         }
     }
+    true
 }
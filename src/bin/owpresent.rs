@@ -33,6 +33,7 @@
 //!   * `1` if present
 //!   * `0` if not present
 //! * errors to stderr
+//! * exit code 0 if every path is present, 1 otherwise (e.g. `owpresent /10.67C6697351FF && ...`)
 //!
 //! ## EXAMPLE
 //! Test presence of a device
@@ -67,34 +68,35 @@
 // MIT Licence
 // {c} 2025 Paul H Alfille
 
-use owrust::console::console_line;
-use owrust::parse_args::{OwDir, Parser};
+use owrust::console::{console_line, exit_code};
+use owrust::parse_args::{OwPresent, Parser};
+use std::process::ExitCode;
 
-fn main() {
+fn main() -> ExitCode {
     let mut owserver = owrust::new(); // create structure for owserver communication
-    let prog = OwDir;
+    let prog = OwPresent;
 
     // configure and get paths
     match prog.command_line(&mut owserver) {
         Ok(paths) => {
             if paths.is_empty() {
                 // No path -- assume root
-                from_path(&mut owserver, "/".to_string());
+                exit_code([from_path(&mut owserver, "/".to_string())])
             } else {
-                // for each pathon command line
-                for path in paths.into_iter() {
-                    from_path(&mut owserver, path);
-                }
+                // for each path on command line
+                exit_code(paths.into_iter().map(|path| from_path(&mut owserver, path)))
             }
         }
         Err(e) => {
             eprintln!("owpresent trouble {}", e);
+            ExitCode::FAILURE
         }
     }
 }
 
-// print 1-wire file contents (e.g. a sensor reading)
-fn from_path(owserver: &mut owrust::OwMessage, path: String) {
+// print presence of a path
+// * false if absent (or a problem)
+fn from_path(owserver: &mut owrust::OwMessage, path: String) -> bool {
     match owserver.present(&path) {
         Ok(values) => {
             if values {
@@ -102,9 +104,11 @@ fn from_path(owserver: &mut owrust::OwMessage, path: String) {
             } else {
                 console_line("0");
             }
+            values
         }
         Err(e) => {
-            eprintln!("Trouble with path {} Error {}", path, e);
+            owserver.trouble(format!("Trouble with path {} Error {}", path, e));
+            false
         }
     }
 }
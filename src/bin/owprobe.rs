@@ -0,0 +1,76 @@
+//! **owprobe** -- _Rust version_
+//!
+//! ## List the 1-wire buses and adapters of an owserver
+//!
+//! **owprobe** is a tool in the 1-wire file system **OWFS**
+//!
+//! This Rust version of **owprobe** is part of **owrust** -- the _Rust language_ OWFS programs
+//! * **OWFS** [documentation](https://owfs.org) and [code](https://github.com/owfs/owfs)
+//! * **owrust** [repository](https://github.com/alfille/owrust)
+//!
+//! ## SYNTAX
+//! ```
+//! owprobe [OPTIONS]
+//! ```
+//! ## PURPOSE
+//! Show what hardware owserver is using
+//! * each physical bus (`/bus.0`, `/bus.1`, ...)
+//! * with the name of its adapter
+//!
+//! ## OPTIONS
+//! * `-s IP:port` (default `localhost:4304`)
+//! * -h           for full list of options
+//!
+//! ## USAGE
+//! * owserver must be running in a network-accessible location
+//! * `owprobe` is a command line program
+//! * output to stdout
+//!   * one line per bus
+//! * errors to stderr
+//!
+//! ## EXAMPLE
+//! ```
+//! owprobe
+//! ```
+//! ```text
+//! /bus.0 DS2490
+//! /bus.1 DS9097U
+//! ```
+//! {c} 2025 Paul H Alfille -- MIT Licence
+
+// owrust project
+// https://github.com/alfille/owrust
+//
+// This is a Rust version of my C owfs code for talking to 1-wire devices via owserver
+// Basically owserver can talk to the physical devices, and provides network access via my "owserver protocol"
+//
+// MIT Licence
+// {c} 2025 Paul H Alfille
+
+use owrust::console::console_line;
+use owrust::parse_args::{OwProbe, Parser};
+
+fn main() {
+    let mut owserver = owrust::new(); // create structure for owserver communication
+    let prog = OwProbe;
+
+    // configure (no paths)
+    match prog.command_line(&mut owserver) {
+        Ok(_) => match owserver.probe() {
+            Ok(buses) => {
+                if buses.is_empty() {
+                    eprintln!("No 1-wire buses found");
+                }
+                for (bus, adapter) in buses {
+                    console_line(format!("{} {}", bus, adapter));
+                }
+            }
+            Err(e) => {
+                eprintln!("Trouble probing buses Error {}", e);
+            }
+        },
+        Err(e) => {
+            eprintln!("owprobe trouble {}", e);
+        }
+    }
+}
@@ -21,8 +21,19 @@
 //! ## OPTIONS
 //! * `-s IP:port` (default `localhost:4304`)
 //! * `--hex       show the value in hexidecimal
+//! * `--raw       write the value bytes unchanged (no newline) e.g. to pipe memory to a file
+//! * `--strict    report an error for non-text values (instead of showing them in hex) and short reads
+//! * `--trim      remove the space padding around values (e.g. `   85.75` becomes `85.75`)
+//! * `--units     add the unit to temperatures and pressures (e.g. `85.75 °F`)
+//! * `-q --quiet` don't show problems with individual paths (exit code is still 1)
+//! * `--csv       write `path,value` rows with a header line
+//! * `--timestamp start each reading with the time (RFC3339 UTC)
+//! * `--epoch     timestamp in seconds since 1970 (implies --timestamp)
+//! * `--interval s  read again every s seconds
+//! * `--interval-align  start on a clock multiple of the interval (e.g. :00, :10, :20)
 //! * `--size n    return only n bytes
-//! * `--offset m  start return at byte m
+//! * `--offset m  start return at byte m (`--size` and `--offset` also take hex, e.g. 0x20)
+//! * `--cache-ttl s  reuse a value read in the last s seconds (no new owserver query)
 //! * -h           for full list of options
 //!
 //! ## PATH
@@ -64,10 +75,11 @@
 // MIT Licence
 // {c} 2025 Paul H Alfille
 
-use owrust::console::console_line;
+use owrust::console::{console_bytes, console_line, exit_code};
 use owrust::parse_args::{OwRead, Parser};
+use std::process::ExitCode;
 
-fn main() {
+fn main() -> ExitCode {
     let mut owserver = owrust::new(); // create structure for owserver communication
     let prog = OwRead;
 
@@ -77,32 +89,132 @@ fn main() {
             if paths.is_empty() {
                 // No path
                 eprintln!("No 1-wire path, so no readings");
+                ExitCode::FAILURE
             } else {
-                // for each pathon command line
-                for path in paths.into_iter() {
-                    from_path(&mut owserver, path);
+                if owserver.csv() {
+                    match owserver.timestamp() {
+                        true => console_line("time,path,value"),
+                        _ => console_line("path,value"),
+                    }
+                }
+                match owserver.poll() {
+                    None => {
+                        // for each path on command line
+                        exit_code(paths.into_iter().map(|path| from_path(&mut owserver, path)))
+                    }
+                    Some(mut poll) => loop {
+                        // repeat at the --interval cadence
+                        poll.wait();
+                        for path in paths.iter() {
+                            from_path(&mut owserver, path.clone());
+                        }
+                    },
                 }
             }
         }
         Err(e) => {
             eprintln!("owread trouble {}", e);
+            ExitCode::FAILURE
         }
     }
 }
 
 // print 1-wire file contents (e.g. a sensor reading)
-fn from_path(owserver: &mut owrust::OwMessage, path: String) {
-    match owserver.read(&path) {
-        Ok(values) => match owserver.show_result(values) {
+// * false if there was a problem
+fn from_path(owserver: &mut owrust::OwMessage, path: String) -> bool {
+    match owserver.read_with_time(&path) {
+        Ok((_, values)) if owserver.raw() => console_bytes(&values),
+        Ok((time, values)) if owserver.csv() => match owserver.show_result(values) {
             Ok(s) => {
-                console_line(s);
+                let row = format!("{},{}", format_csv(&path), format_csv(s.trim()));
+                match owserver.timestamp() {
+                    true => console_line(format!("{},{}", owserver.stamp(time).trim(), row)),
+                    _ => console_line(row),
+                }
             }
             Err(e) => {
-                eprintln!("Reading error {}", e);
+                owserver.trouble(format!("Reading error {}", e));
+                return false;
+            }
+        },
+        Ok((time, values)) => match owserver.show_result(values) {
+            Ok(s) => {
+                let s = owserver.show_units(&path, s);
+                console_line(format!("{}{}", owserver.stamp(time), s));
+            }
+            Err(e) => {
+                owserver.trouble(format!("Reading error {}", e));
+                return false;
             }
         },
         Err(e) => {
-            eprintln!("Trouble with path {} Error {}", path, e);
+            owserver.trouble(format!("Trouble with path {} Error {}", path, e));
+            return false;
         }
     }
+    true
+}
+
+// CSV field (RFC 4180)
+// * quoted if it contains a comma, quote or line break
+// * embedded quotes are doubled
+fn format_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    // owserver that answers every query with an error code (e.g. -2 no such path)
+    fn failing_owserver(ret: i32) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut header = [0u8; 24];
+                if stream.read_exact(&mut header).is_err() {
+                    continue;
+                }
+                let payload = i32::from_be_bytes(header[4..8].try_into().unwrap()).max(0);
+                let mut content = vec![0u8; payload as usize];
+                if stream.read_exact(&mut content).is_err() {
+                    continue;
+                }
+                let mut response = [0u8; 24];
+                response[8..12].copy_from_slice(&ret.to_be_bytes());
+                let _ = stream.write_all(&response);
+            }
+        });
+        address
+    }
+
+    #[test]
+    fn csv_test() {
+        assert_eq!(format_csv("85.7961"), "85.7961");
+        assert_eq!(
+            format_csv("/10.67C6697351FF/temperature"),
+            "/10.67C6697351FF/temperature"
+        );
+        assert_eq!(format_csv("1,2"), "\"1,2\"");
+        assert_eq!(format_csv("say \"hi\", ok"), "\"say \"\"hi\"\", ok\"");
+        assert_eq!(format_csv("48 65 6C"), "48 65 6C");
+    }
+
+    #[test]
+    fn missing_path_test() {
+        let address = failing_owserver(-2);
+        let mut owserver = owrust::OwMessage::builder().server(&address).build();
+        assert!(!from_path(
+            &mut owserver,
+            "/10.FFFFFFFFFFFF/temperature".to_string()
+        ));
+    }
 }
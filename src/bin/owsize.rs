@@ -13,48 +13,40 @@
 //! owsize [OPTIONS] PATH
 //! ```
 //! ## PURPOSE
-//! Return sie of data (in bytes) for a path read
+//! Return size of data (in bytes) for a path read
+//! * useful to see how big a memory read would be
 //!
 //! ## OPTIONS
 //! * `-s IP:port` (default `localhost:4304`)
+//! * `--human`    show sizes with units (e.g. `2.0 KiB`)
 //! * -h           for full list of options
 //!
 //! ## PATH
 //! * 1-wire path to a file
-//! * No Default
+//! * Default is root directory
 //! * More than one path can be given
 //!
-//! **owpresent** works on files and directories.
-//!
 //! ## USAGE
 //! * owserver must be running in a network-accessible location
-//! * `owpresent` is a command line program
+//! * `owsize` is a command line program
 //! * output to stdout
-//!   * `1` if present
-//!   * `0` if not present
+//!   * size in bytes
 //! * errors to stderr
 //!
 //! ## EXAMPLE
-//! Test presence of a device
-//! ```
-//! owpresent /10.67C6697351FF
-//! ```
-//! ```text
-//! 1
-//! ```
-//! Test a file
+//! Size of a temperature reading
 //! ```
-//! owpresent /10.67C6697351FF/temperature
+//! owsize /10.67C6697351FF/temperature
 //! ```
 //! ```text
-//! 1
+//! 12
 //! ```
-//! Test a device that isn't there
+//! Size of a memory with units
 //! ```
-//! owpresent /10.FFFFFFFFFFFF
+//! owsize --human /23.5F2F2F2F2F2F/memory
 //! ```
 //! ```text
-//! 0
+//! 512 B
 //! ```
 //! {c} 2025 Paul H Alfille -- MIT Licence
 
@@ -67,10 +59,11 @@
 // MIT Licence
 // {c} 2025 Paul H Alfille
 
-use owrust::console::console_line;
+use owrust::console::{console_line, exit_code};
 use owrust::parse_args::{OwSize, Parser};
+use std::process::ExitCode;
 
-fn main() {
+fn main() -> ExitCode {
     let mut owserver = owrust::new(); // create structure for owserver communication
     let prog = OwSize;
 
@@ -79,28 +72,29 @@ fn main() {
         Ok(paths) => {
             if paths.is_empty() {
                 // No path -- assume root
-                from_path(&mut owserver, "/".to_string());
+                exit_code([from_path(&mut owserver, "/".to_string())])
             } else {
-                // for each pathon command line
-                for path in paths.into_iter() {
-                    from_path(&mut owserver, path);
-                }
+                // for each path on command line
+                exit_code(paths.into_iter().map(|path| from_path(&mut owserver, path)))
             }
         }
         Err(e) => {
-            eprintln!("owpresent trouble {}", e);
+            eprintln!("owsize trouble {}", e);
+            ExitCode::FAILURE
         }
     }
 }
 
-// print 1-wire file contents (e.g. a sensor reading)
-fn from_path(owserver: &mut owrust::OwMessage, path: String) {
-    match owserver.present(&path) {
+// print the expected length of a read
+fn from_path(owserver: &mut owrust::OwMessage, path: String) -> bool {
+    match owserver.size(&path) {
         Ok(value) => {
-            console_line(format!("{}", value));
+            console_line(owserver.show_size(value));
         }
         Err(e) => {
-            eprintln!("Trouble with path {} Error {}", path, e);
+            owserver.trouble(format!("Trouble with path {} Error {}", path, e));
+            return false;
         }
     }
+    true
 }
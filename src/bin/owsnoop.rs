@@ -30,6 +30,11 @@
 //! * requests from client (like owdir for a directory)
 //! * responses from owserver
 //!
+//! ## OPTIONS
+//! * `--workers N` connections handled at once (default: number of CPUs)
+//!   * further connections wait their turn
+//! * `--heartbeat SECONDS` status line on stderr (address and connections handled) this often
+//!
 //! ## USAGE
 //! * owserver must be running in a network-accessible location
 //! * `owsnoop` is a command line program
@@ -49,12 +54,12 @@
 //! ```text
 //! Query Message incoming Version: 10001 tokens=1
 //! DIRALL /
-//! Flags: C mbar f.i net   alias  bus
+//! Flags: C mbar f.i net alias bus
 //! Payload:2 Size:0 Offset:0
 //!
 //! Response Message Incoming Version: 0
 //! Return code = 0
-//! Flags: C mbar f.i net   alias  bus
+//! Flags: C mbar f.i net alias bus
 //! Payload:113 Size:112 Offset:32770
 //!
 //! ```
@@ -66,12 +71,12 @@
 //! ```text
 //! Query Message incoming Version: 10001 tokens=1
 //! READ /10.67c6697351ff/temperature
-//! Flags: C mbar f.i net   alias  bus
+//! Flags: C mbar f.i net alias bus
 //! Payload:29 Size:65536 Offset:0
 //!
 //! Response Message Incoming Version: 0
 //! Return code = 12
-//! Flags: C mbar f.i net   alias  bus
+//! Flags: C mbar f.i net alias bus
 //! Payload:12 Size:12 Offset:0
 //!
 //! ```
@@ -101,7 +106,7 @@ fn main() {
             match owserver.listen() {
                 Ok(_x) => (),
                 Err(e) => {
-                    eprintln!("No listening address given (e.g. -p localhost:14304) {}", e);
+                    eprintln!("owsnoop cannot listen: {}", e);
                 }
             }
         }
@@ -20,7 +20,12 @@
 //! * `-s IP:port` (default `localhost:4304`)
 //! * `--dir`      Add trailing **/** for directory elements
 //! * `--bare`     Suppress non-device entries
+//! * `--no-bus`   Ask owserver to leave out `bus.N` entries
+//! * `--dir-cache-ttl SECONDS` Reuse a directory listing fetched within SECONDS
 //! * `--prune`    Even more spare output suppressing convenience files like `id` and `crc`
+//! * `--color WHEN` auto (default, only on a terminal), always or never
+//!   * devices bold, directories blue
+//! * `--style STYLE` unicode (default), ascii (`+--` lines) or indent (spaces only)
 //! * -h           for full list of options
 //!
 //! ## PATH
@@ -154,10 +159,14 @@
 // This is a Rust version of my C owfs code for talking to 1-wire devices via owserver
 // Basically owserver can talk to the physical devices, and provides network access via my "owserver protocol"
 
-use owrust::console::console_line;
+use owrust::console::{console_result, exit_code, Console};
+use owrust::message::TreeStyle;
 use owrust::parse_args::{OwTree, Parser};
+use owrust::tree::{DirEntry, EntryKind, TreeWalker};
+use std::io::Write;
+use std::process::ExitCode;
 
-fn main() {
+fn main() -> ExitCode {
     let mut owserver = owrust::new(); // create structure for owserver communication
     let prog = OwTree;
 
@@ -166,130 +175,248 @@ fn main() {
         Ok(paths) => {
             if paths.is_empty() {
                 // No path -- assume root
-                from_path(&mut owserver, "/".to_string());
+                exit_code([from_path(&mut owserver, "/".to_string())])
             } else {
                 // show tree for each path
-                for path in paths.into_iter() {
-                    from_path(&mut owserver, path);
-                }
+                exit_code(paths.into_iter().map(|path| from_path(&mut owserver, path)))
             }
         }
         Err(e) => {
             eprintln!("owtree trouble {}", e);
+            ExitCode::FAILURE
         }
     }
 }
 
 // start at path, printing and following directories recursively
-fn from_path(owserver: &mut owrust::OwMessage, path: String) {
-    let root = File::root(path);
-    root.root_print(owserver);
+// * false if any directory couldn't be listed
+fn from_path(owserver: &mut owrust::OwMessage, path: String) -> bool {
+    let color = owserver.color();
+    let quiet = owserver.quiet();
+    let lines = Lines::new(owserver.tree_style());
+    let mut ok = true;
+    let mut walker = TreeWalker::new(owserver, &path);
+    // problems are reported as the walk meets them
+    let entries = std::iter::from_fn(|| {
+        let item = walker.next();
+        for e in walker.take_errors() {
+            ok = false;
+            if !quiet {
+                eprintln!("{}", e);
+            }
+        }
+        item
+    });
+    console_result(print_tree(entries, color, &lines, &mut Console));
+    ok
+}
+
+// draw the tree of (depth, entry) in walk order
+// * depth 0 (the starting path) has no tree lines
+fn print_tree<I>(entries: I, color: bool, lines: &Lines, out: &mut dyn Write) -> std::io::Result<()>
+where
+    I: Iterator<Item = (usize, DirEntry)>,
+{
+    // is each enclosing directory the last of its own directory?
+    let mut lasts: Vec<bool> = Vec::new();
+    for (depth, entry) in entries {
+        if depth == 0 {
+            writeln!(out, "{}", paint(&entry, color))?;
+            continue;
+        }
+        lasts.truncate(depth - 1);
+        writeln!(
+            out,
+            "{}{}",
+            lines.prefix(&lasts, entry.last),
+            paint(&entry, color)
+        )?;
+        lasts.push(entry.last);
+    }
+    Ok(())
 }
 
-#[derive(Debug, Clone)]
-// Structure for a directory
-struct Dir {
-    contents: Vec<File>,
+// connectors drawn in front of entries (from _--style_)
+struct Lines {
+    end: &'static str,
+    rgt: &'static str,
+    next: &'static str,
+    tab: &'static str,
 }
-impl Dir {
-    // directory needs to call dirall to get a list of contents
-    fn new(owserver: &mut owrust::OwMessage, path: String) -> Self {
-        match owserver.dirallslash(&path) {
-            Ok(d) => Dir {
-                contents: d.into_iter().map(File::new).collect(),
+
+impl Lines {
+    fn new(style: &TreeStyle) -> Self {
+        match style {
+            TreeStyle::UNICODE => Lines {
+                end: "└── ",
+                rgt: "│   ",
+                next: "├── ",
+                tab: "    ",
+            },
+            TreeStyle::ASCII => Lines {
+                end: "\\-- ",
+                rgt: "|   ",
+                next: "+-- ",
+                tab: "    ",
+            },
+            TreeStyle::INDENT => Lines {
+                end: "    ",
+                rgt: "    ",
+                next: "    ",
+                tab: "    ",
             },
-            Err(e) => {
-                eprintln!("Trouble reading directory {}: {} ", &path, e);
-                Dir::null_dir()
-            }
         }
     }
-    fn null_dir() -> Self {
-        Dir { contents: vec![] }
-    }
-    // print each file in directory
-    fn print(&self, owserver: &mut owrust::OwMessage, prefix: &String) {
-        let len = self.contents.len();
-        for (i, f) in self.contents.iter().enumerate() {
-            f.print(owserver, prefix, i == len - 1);
-        }
+
+    // tree lines in front of an entry
+    fn prefix(&self, lasts: &[bool], last: bool) -> String {
+        let mut line: String = lasts
+            .iter()
+            .map(|&l| match l {
+                true => self.tab,
+                false => self.rgt,
+            })
+            .collect();
+        line.push_str(match last {
+            true => self.end,
+            false => self.next,
+        });
+        line
     }
 }
 
-#[derive(Debug, Clone)]
-// file structure for each entry
-struct File {
-    path: String, // full path
-    name: String, // filename itself (for display)
-    dir: bool,    // is this a directory?
+// entry name, in ANSI color if wanted
+// * devices bold, directories blue, files plain
+fn paint(entry: &DirEntry, color: bool) -> String {
+    let code = if !color {
+        None
+    } else if entry.kind == EntryKind::Device {
+        Some(BOLD)
+    } else if entry.dir {
+        Some(BLUE)
+    } else {
+        None
+    };
+    match code {
+        Some(code) => format!("{}{}{}", code, entry.name, RESET),
+        None => entry.name.clone(),
+    }
 }
-impl File {
-    // parse file
-    fn new(path: String) -> Self {
-        let parts: Vec<String> = path.split('/').map(String::from).collect();
-        let len = parts.len();
-        if len == 0 {
-            File {
-                path,
-                name: "No name".to_string(),
-                dir: false,
-            }
-        } else if len == 1 {
-            File {
-                path,
-                name: parts[0].clone(),
-                dir: false,
-            }
-        } else if parts[len - 1].is_empty() {
-            // directory since null last element
-            File {
-                path,
-                name: parts[len - 2].clone(),
-                dir: true,
-            }
-        } else {
-            // regular file
-            File {
-                path,
-                name: parts[len - 1].clone(),
-                dir: false,
-            }
+
+const BOLD: &str = "\x1b[1m";
+const BLUE: &str = "\x1b[34m";
+const RESET: &str = "\x1b[0m";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, dir: bool) -> DirEntry {
+        DirEntry {
+            path: format!("/{}", name),
+            name: name.to_string(),
+            dir,
+            last: false,
+            kind: EntryKind::of(name, dir),
         }
     }
-    fn root(path: String) -> Self {
-        File {
-            path: path.clone(),
-            name: path.clone(),
-            dir: true,
+
+    #[test]
+    fn paint_test() {
+        let entries = [
+            entry("10.67C6697351FF", true),
+            entry("errata", true),
+            entry("temperature", false),
+        ];
+        // never
+        for e in entries.iter() {
+            assert_eq!(paint(e, false), e.name);
+            assert!(!paint(e, false).contains('\x1b'));
         }
+        // always
+        assert_eq!(
+            paint(&entries[0], true),
+            format!("{}10.67C6697351FF{}", BOLD, RESET)
+        );
+        assert_eq!(paint(&entries[1], true), format!("{}errata{}", BLUE, RESET));
+        assert_eq!(paint(&entries[2], true), "temperature");
     }
-    fn root_print(&self, owserver: &mut owrust::OwMessage) {
-        // File
-        console_line(&self.name);
-        let dir = Dir::new(owserver, self.path.clone());
-        dir.print(owserver, &"".to_string());
+
+    // same mock walk for each style
+    fn render(style: TreeStyle) -> String {
+        let walk = [
+            (0, "/", true, true),
+            (1, "10.67C6697351FF", true, false),
+            (2, "errata", true, false),
+            (3, "die", false, true),
+            (2, "temperature", false, true),
+            (1, "05.4AEC29CDBAAB", true, true),
+            (2, "PIO", false, true),
+        ]
+        .map(|(depth, name, dir, last)| {
+            let mut e = entry(name, dir);
+            e.last = last;
+            (depth, e)
+        });
+        let mut out: Vec<u8> = Vec::new();
+        print_tree(walk.into_iter(), false, &Lines::new(&style), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
     }
-    // print each file with appropriate structure "prefix"
-    fn print(&self, owserver: &mut owrust::OwMessage, prefix: &String, last: bool) {
-        // File name printed
-        if last {
-            console_line(format!("{}{}{}", prefix, END, self.name));
-        } else {
-            console_line(format!("{}{}{}", prefix, NEXT, self.name));
-        }
-        // Dir followed
-        if self.dir {
-            let prefix: String = match last {
-                true => format!("{}{}", prefix, TAB),
-                false => format!("{}{}", prefix, RGT),
-            };
-            let dir = Dir::new(owserver, self.path.clone());
-            dir.print(owserver, &prefix);
-        }
+
+    #[test]
+    fn print_tree_test() {
+        assert_eq!(
+            render(TreeStyle::UNICODE),
+            [
+                "/",
+                "├── 10.67C6697351FF",
+                "│   ├── errata",
+                "│   │   └── die",
+                "│   └── temperature",
+                "└── 05.4AEC29CDBAAB",
+                "    └── PIO",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            render(TreeStyle::ASCII),
+            [
+                "/",
+                "+-- 10.67C6697351FF",
+                "|   +-- errata",
+                "|   |   \\-- die",
+                "|   \\-- temperature",
+                "\\-- 05.4AEC29CDBAAB",
+                "    \\-- PIO",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            render(TreeStyle::INDENT),
+            [
+                "/",
+                "    10.67C6697351FF",
+                "        errata",
+                "            die",
+                "        temperature",
+                "    05.4AEC29CDBAAB",
+                "        PIO",
+                "",
+            ]
+            .join("\n")
+        );
     }
-}
 
-const END: &str = "└── ";
-const RGT: &str = "│   ";
-const NEXT: &str = "├── ";
-const TAB: &str = "    ";
+    #[test]
+    fn prefix_test() {
+        let lines = Lines::new(&TreeStyle::UNICODE);
+        assert_eq!(lines.prefix(&[], false), lines.next);
+        assert_eq!(lines.prefix(&[], true), lines.end);
+        assert_eq!(
+            lines.prefix(&[false, true], true),
+            format!("{}{}{}", lines.rgt, lines.tab, lines.end)
+        );
+    }
+}
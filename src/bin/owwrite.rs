@@ -18,6 +18,7 @@
 //! * `--hex       read the value in hexidecimal
 //! * `--size n    write only n bytes
 //! * `--offset m  start writing at byte m
+//! * `--dry-run`  show the message that would be sent (path, bytes, offset) without writing
 //! * -h           for full list of options
 //!
 //! ## PATH
@@ -32,6 +33,7 @@
 //!   * no spaces between bytes
 //!
 //! ### More than one PATH / VALUE pair allowed
+//! * a final PATH without a VALUE is reported (exit code 1) after the other pairs are written
 //!
 //! **owwrite** only works on files.
 //!
@@ -60,9 +62,11 @@
 // MIT Licence
 // {c} 2025 Paul H Alfille
 
+use owrust::console::{console_lines, exit_code};
 use owrust::parse_args::{OwWrite, Parser};
+use std::process::ExitCode;
 
-fn main() {
+fn main() -> ExitCode {
     let mut owserver = owrust::new(); // create structure for owserver communication
     let prog = OwWrite;
 
@@ -72,30 +76,77 @@ fn main() {
             if paths.is_empty() {
                 // No path
                 eprintln!("Not enough arguments");
-            } else if !paths.len().is_multiple_of(2) {
-                eprintln!("Path and value not paired");
+                ExitCode::FAILURE
             } else {
                 // for each path/value pair in command line
-                for chunk in paths.chunks(2) {
-                    from_path(&mut owserver, &chunk[0], &chunk[1]);
-                }
+                let (pairs, unpaired) = pairs(&paths);
+                let written = pairs
+                    .into_iter()
+                    .map(|(path, value)| from_path(&mut owserver, path, value));
+                // then the path left without a value (a failure)
+                let unpaired = unpaired.map(|path| {
+                    eprintln!("Path {} has no value -- not written", path);
+                    false
+                });
+                exit_code(written.chain(unpaired))
             }
         }
         Err(e) => {
-            eprintln!("owread trouble {}", e);
+            eprintln!("owwrite trouble {}", e);
+            ExitCode::FAILURE
         }
     }
 }
 
+// path/value pairs from the command line
+// * a trailing path without a value is returned separately
+fn pairs(args: &[String]) -> (Vec<(&String, &String)>, Option<&String>) {
+    let chunks = args.chunks_exact(2);
+    let unpaired = chunks.remainder().first();
+    (chunks.map(|c| (&c[0], &c[1])).collect(), unpaired)
+}
+
 // print 1-wire file contents (e.g. a sensor reading)
-fn from_path(owserver: &mut owrust::OwMessage, path: &String, value: &String) {
-    match owserver.write(path, value.as_bytes()) {
-        Ok(_) => (),
+fn from_path(owserver: &mut owrust::OwMessage, path: &String, value: &String) -> bool {
+    let written = match owserver.dry_run() {
+        true => owserver
+            .write_preview(path, value.as_bytes())
+            .map(console_lines),
+        _ => owserver.write(path, value.as_bytes()),
+    };
+    match written {
+        Ok(_) => true,
         Err(e) => {
-            eprintln!(
+            owserver.trouble(format!(
                 "Trouble with write -- path {} value {} Error {}",
                 path, value, e
-            );
+            ));
+            false
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pairs_test() {
+        let args: Vec<String> = [
+            "/05.4AEC29CDBAAB/PIO",
+            "1",
+            "/12.1A2B3C4D5E6F/PIO.A",
+            "0",
+            "/3A.0102",
+        ]
+        .map(String::from)
+        .to_vec();
+        let (pairs, unpaired) = pairs(&args);
+        assert_eq!(pairs, vec![(&args[0], &args[1]), (&args[2], &args[3])]);
+        assert_eq!(unpaired, Some(&args[4]));
+
+        let (pairs, unpaired) = super::pairs(&args[..4]);
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(unpaired, None);
+    }
+}
@@ -6,6 +6,9 @@
 //! * CLI safe for broken_pipe
 //!   * Can Ctrl-C without error
 //!   * **head** does not cause error
+//! * `try_` versions return the error instead of exiting
+//!   * usable from library and test code
+//! * Output can be sent to a file instead (**console_to_file**)
 //! * Thread-safe -- does not intermix individual text output
 //! * Initializes and locks automatically
 //!
@@ -20,16 +23,89 @@
 // MIT Licence
 // {c} 2025 Paul H Alfille
 
-use std::io::{self, ErrorKind, Stdout, Write};
-use std::process;
+use std::fs::File;
+use std::io::{self, ErrorKind, IsTerminal, Write};
+use std::process::{self, ExitCode};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Mutex, OnceLock};
 
+/// Destination for console output (stdout unless changed)
+type Sink = Box<dyn Write + Send>;
+
 /// local shared state
-static GLOBAL_STDOUT: OnceLock<Mutex<Stdout>> = OnceLock::new();
+static GLOBAL_STDOUT: OnceLock<Mutex<Sink>> = OnceLock::new();
+
+/// is output going to stdout (not a file or other writer)?
+static TO_STDOUT: AtomicBool = AtomicBool::new(true);
 
 /// Internal initialization of mutex
-fn get_handle() -> &'static Mutex<Stdout> {
-    GLOBAL_STDOUT.get_or_init(|| Mutex::new(io::stdout()))
+fn get_handle() -> &'static Mutex<Sink> {
+    GLOBAL_STDOUT.get_or_init(|| Mutex::new(Box::new(io::stdout())))
+}
+
+/// ### set_console_writer
+/// Send all further console output to a different writer
+/// * e.g. a file
+/// * the writer is never treated as a terminal (see **console_is_terminal**)
+/// * **console_to_stdout** restores the default
+pub fn set_console_writer(writer: Box<dyn Write + Send>) {
+    swap_writer(writer);
+    TO_STDOUT.store(false, Ordering::Relaxed);
+}
+
+/// ### console_to_stdout
+/// Send all further console output to stdout (the default)
+pub fn console_to_stdout() {
+    swap_writer(Box::new(io::stdout()));
+    TO_STDOUT.store(true, Ordering::Relaxed);
+}
+
+/// ### console_is_terminal
+/// Is console output going to a terminal?
+/// * false for a file (the _--output_ option) or other writer
+/// * e.g. to decide on color
+pub fn console_is_terminal() -> bool {
+    TO_STDOUT.load(Ordering::Relaxed) && io::stdout().is_terminal()
+}
+
+fn swap_writer(writer: Sink) {
+    // aquire mutex
+    let mut guard = get_handle().lock().expect("Mutex poisoned");
+    let _ = guard.flush();
+    *guard = writer;
+}
+
+/// ### console_to_file
+/// Send all further console output to a file (the _--output_ option)
+/// * file is created or truncated
+/// * returns IO error if the file can't be created
+pub fn console_to_file(path: &str) -> io::Result<()> {
+    let file = File::create(path)?;
+    set_console_writer(Box::new(file));
+    Ok(())
+}
+
+/// ### Console
+/// `std::io::Write` to the console destination (stdout or the _--output_ file)
+/// * for code that renders to any writer
+/// * errors are returned -- pass the result to **console_result**
+pub struct Console;
+
+impl Write for Console {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        get_handle().lock().expect("Mutex poisoned").write(buf)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        get_handle().lock().expect("Mutex poisoned").flush()
+    }
+}
+
+/// ### console_result
+/// Handle the result of writing to **Console** like the other console functions
+/// * clean program exit (0) on Broken Pipe
+/// * annotated program exit (1) on other IO errors
+pub fn console_result(result: io::Result<()>) {
+    handle_io_result(result);
 }
 
 /// Internal helper to handle IO errors specifically for BrokenPipe
@@ -53,27 +129,43 @@ fn handle_io_result(result: io::Result<()>) {
 /// Simple single line output
 /// * atomic output to stdout
 /// * string-like input (&str, String,...)
+/// * exits on error (see **try_console_line**)
 /// #### Example
 /// ```
 /// use owrust::console_line;
 /// console_line("Hello");
 ///```
 pub fn console_line<S>(message: S)
+where
+    S: AsRef<str>,
+{
+    handle_io_result(try_console_line(message));
+}
+
+/// ### try_console_line
+/// Simple single line output
+/// * atomic output to stdout
+/// * returns IO errors (including BrokenPipe) rather than exiting
+/// #### Example
+/// ```
+/// use owrust::console::try_console_line;
+/// try_console_line("Hello").expect("stdout closed");
+///```
+pub fn try_console_line<S>(message: S) -> io::Result<()>
 where
     S: AsRef<str>,
 {
     // aquire mutex
     let mut guard = get_handle().lock().expect("Mutex poisoned");
 
-    // Use writeln! and pass the result to our handler
-    let result = writeln!(guard, "{}", message.as_ref());
-    handle_io_result(result);
+    write_lines(&mut *guard, [message])
 }
 
 /// ### console_lines
 /// Write a series of lines to the console (stdout) atomically
 /// * Generic function: Works with anything that can be treated as a string slice
 /// * Handles Broken Pipe gracefully
+/// * exits on error (see **try_console_lines**)
 /// #### Example
 /// ```
 /// use owrust::console_lines;
@@ -85,6 +177,17 @@ where
 /// console_lines(&text_lines);
 ///```
 pub fn console_lines<T, S>(lines: T)
+where
+    T: IntoIterator<Item = S>, // Can be iterated over
+    S: AsRef<str>,             // Items can be seen as &str
+{
+    handle_io_result(try_console_lines(lines));
+}
+
+/// ### try_console_lines
+/// Write a series of lines to the console (stdout) atomically
+/// * stops at the first IO error and returns it rather than exiting
+pub fn try_console_lines<T, S>(lines: T) -> io::Result<()>
 where
     T: IntoIterator<Item = S>, // Can be iterated over
     S: AsRef<str>,             // Items can be seen as &str
@@ -92,8 +195,173 @@ where
     // aquire mutex
     let mut guard = get_handle().lock().expect("Mutex poisoned");
 
+    write_lines(&mut *guard, lines)
+}
+
+// Write each line with a newline, stopping at the first error
+fn write_lines<W, T, S>(out: &mut W, lines: T) -> io::Result<()>
+where
+    W: Write,
+    T: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
     for line in lines {
-        let result = writeln!(guard, "{}", line.as_ref());
-        handle_io_result(result);
+        writeln!(out, "{}", line.as_ref())?;
+    }
+    Ok(())
+}
+
+/// ### console_bytes
+/// Write bytes to the console (stdout) unchanged
+/// * no trailing newline and no UTF-8 check
+/// * suitable for binary data (e.g. device memory piped to a file)
+/// * Handles Broken Pipe gracefully
+/// #### Example
+/// ```
+/// use owrust::console::console_bytes;
+/// console_bytes(&[0x48, 0x69, 0x0A]);
+///```
+pub fn console_bytes(bytes: &[u8]) {
+    // aquire mutex
+    let mut guard = get_handle().lock().expect("Mutex poisoned");
+
+    let result = write_bytes(&mut *guard, bytes);
+    handle_io_result(result);
+}
+
+// bytes as given, no newline added
+pub(crate) fn write_bytes<W: Write + ?Sized>(out: &mut W, bytes: &[u8]) -> io::Result<()> {
+    out.write_all(bytes)?;
+    out.flush()
+}
+
+/// ### join_separated
+/// Join entries into bytes, ending each with the separator
+/// * separator `b'\n'` gives normal lines
+/// * separator `b'\0'` matches `find -print0` for `xargs -0`
+/// #### Example
+/// ```
+/// use owrust::console::join_separated;
+/// assert_eq!(join_separated(["a", "b"], b'\0'), b"a\0b\0");
+///```
+pub fn join_separated<T, S>(lines: T, separator: u8) -> Vec<u8>
+where
+    T: IntoIterator<Item = S>, // Can be iterated over
+    S: AsRef<str>,             // Items can be seen as &str
+{
+    let mut bytes: Vec<u8> = Vec::new();
+    for line in lines {
+        bytes.extend_from_slice(line.as_ref().as_bytes());
+        bytes.push(separator);
+    }
+    bytes
+}
+
+/// ### console_separated
+/// Write a series of entries to the console (stdout) atomically
+/// * each entry ends with the separator byte (see **join_separated**)
+/// * Handles Broken Pipe gracefully
+pub fn console_separated<T, S>(lines: T, separator: u8)
+where
+    T: IntoIterator<Item = S>, // Can be iterated over
+    S: AsRef<str>,             // Items can be seen as &str
+{
+    console_bytes(&join_separated(lines, separator));
+}
+
+/// ### exit_code
+/// Program exit code from the success of each path
+/// * SUCCESS only if every path succeeded, else FAILURE (1)
+/// * every result is taken, so all paths are handled even after a failure
+pub fn exit_code<T>(results: T) -> ExitCode
+where
+    T: IntoIterator<Item = bool>,
+{
+    let failures = results.into_iter().filter(|ok| !ok).count();
+    match failures {
+        0 => ExitCode::SUCCESS,
+        _ => ExitCode::FAILURE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_code_test() {
+        assert_eq!(exit_code([]), ExitCode::SUCCESS);
+        assert_eq!(exit_code([true, true]), ExitCode::SUCCESS);
+        assert_eq!(exit_code([true, false, true]), ExitCode::FAILURE);
+        assert_eq!(exit_code([false]), ExitCode::FAILURE);
+
+        // no short cut after a failure
+        let mut handled = 0;
+        let code = exit_code([false, true, true].into_iter().inspect(|_| handled += 1));
+        assert_eq!(code, ExitCode::FAILURE);
+        assert_eq!(handled, 3);
+    }
+
+    // Writer that fails like a closed pipe
+    struct ClosedPipe;
+    impl Write for ClosedPipe {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::from(ErrorKind::BrokenPipe))
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_error_test() {
+        let e = write_lines(&mut ClosedPipe, ["Hello"]).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::BrokenPipe);
+
+        let mut out: Vec<u8> = Vec::new();
+        write_lines(&mut out, ["Hello", "World"]).unwrap();
+        assert_eq!(out, b"Hello\nWorld\n".to_vec());
+    }
+
+    // the console sink is process-wide: tests that use or move it take turns
+    static SINK: Mutex<()> = Mutex::new(());
+    fn sink() -> std::sync::MutexGuard<'static, ()> {
+        SINK.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    #[test]
+    fn try_console_test() {
+        let _sink = sink();
+        assert!(try_console_line("try_console_line").is_ok());
+        assert!(try_console_lines(["try_console_lines"]).is_ok());
+    }
+
+    #[test]
+    fn file_output_test() {
+        let _sink = sink();
+        let path = std::env::temp_dir().join(format!("owrust_console_{}.txt", process::id()));
+        let name = path.to_str().unwrap();
+        console_to_file(name).unwrap();
+        try_console_line("file_output_test line").unwrap();
+        assert!(!console_is_terminal());
+        console_to_stdout();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(text.contains("file_output_test line\n"));
+    }
+
+    #[test]
+    fn separator_test() {
+        let entries: Vec<String> = vec!["/10.67C6697351FF".to_string(), "/bus.0".to_string()];
+        assert_eq!(
+            join_separated(&entries, b'\n'),
+            b"/10.67C6697351FF\n/bus.0\n".to_vec()
+        );
+        assert_eq!(
+            join_separated(&entries, b'\0'),
+            b"/10.67C6697351FF\0/bus.0\0".to_vec()
+        );
+        assert!(join_separated(Vec::<String>::new(), b'\0').is_empty());
     }
 }
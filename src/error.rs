@@ -49,6 +49,10 @@ pub type OwEResult<T> = std::result::Result<T, OwError>;
 /// the **owrust**-specific error type
 ///
 /// details field is a String with error details
+/// * Server holds an owserver return code and its meaning
+/// * more variants may be added -- include a `_` arm when matching
+/// * **general**, **input**, **output**, **numeric** and **text** build the String variants
+#[non_exhaustive]
 pub enum OwError {
     General(String),
     Input(String),
@@ -57,6 +61,92 @@ pub enum OwError {
     Args(pico_args::Error),
     Numeric(String),
     Text(String),
+    Server { code: i32, meaning: &'static str },
+}
+
+impl OwError {
+    /// ### io_kind
+    /// the underlying `io::ErrorKind` of a network or file error
+    /// * e.g. `TimedOut` or `ConnectionRefused` to decide whether to retry
+    /// * None for other errors
+    pub fn io_kind(&self) -> Option<std::io::ErrorKind> {
+        match self {
+            OwError::Io(e) => Some(e.kind()),
+            _ => None,
+        }
+    }
+
+    /// ### general
+    /// General error from a message (`&str` or `String`)
+    pub fn general(details: impl Into<String>) -> Self {
+        OwError::General(details.into())
+    }
+
+    /// ### input
+    /// Input (configuration or command line) error
+    pub fn input(details: impl Into<String>) -> Self {
+        OwError::Input(details.into())
+    }
+
+    /// ### output
+    /// Output (or owserver response) error
+    pub fn output(details: impl Into<String>) -> Self {
+        OwError::Output(details.into())
+    }
+
+    /// ### numeric
+    /// Error converting a number
+    pub fn numeric(details: impl Into<String>) -> Self {
+        OwError::Numeric(details.into())
+    }
+
+    /// ### text
+    /// Error converting text
+    pub fn text(details: impl Into<String>) -> Self {
+        OwError::Text(details.into())
+    }
+
+    /// ### server
+    /// Server error from an owserver (negative) return code
+    pub fn server(code: i32) -> Self {
+        OwError::Server {
+            code,
+            meaning: server_meaning(code),
+        }
+    }
+}
+
+/// ### server_meaning
+/// Meaning of an owserver return code
+/// * owserver returns negative errno values (Linux numbering)
+/// * unusual codes are "Unknown error"
+pub fn server_meaning(code: i32) -> &'static str {
+    match -code {
+        1 => "Operation not permitted",
+        2 => "No such file or directory",
+        5 => "Input/output error",
+        7 => "Argument list too long",
+        12 => "Cannot allocate memory",
+        13 => "Permission denied",
+        14 => "Bad address",
+        16 => "Device or resource busy",
+        17 => "File exists",
+        19 => "No such device",
+        20 => "Not a directory",
+        21 => "Is a directory",
+        22 => "Invalid argument",
+        34 => "Numerical result out of range",
+        36 => "File name too long",
+        38 => "Function not implemented",
+        42 => "No message of desired type",
+        61 => "No data available",
+        71 => "Protocol error",
+        74 => "Bad message",
+        75 => "Value too large for defined data type",
+        95 => "Operation not supported",
+        110 => "Connection timed out",
+        _ => "Unknown error",
+    }
 }
 
 impl fmt::Display for OwError {
@@ -69,6 +159,10 @@ impl fmt::Display for OwError {
             OwError::Args(e) => write!(f, "Args error: {}", e),
             OwError::Text(e) => write!(f, "Text conversion error: {}", e),
             OwError::Numeric(e) => write!(f, "Non-numeric characters: {}", e),
+            OwError::Server { code, meaning } => match *meaning {
+                "Unknown error" => write!(f, "owserver: Unknown error {}", code),
+                _ => write!(f, "owserver: {}", meaning),
+            },
         }
     }
 }
@@ -101,16 +195,61 @@ impl From<pico_args::Error> for OwError {
 }
 impl From<std::str::Utf8Error> for OwError {
     fn from(_e: std::str::Utf8Error) -> Self {
-        OwError::Text("Utf8 Error".into())
+        OwError::text("Utf8 Error")
     }
 }
 impl From<std::string::FromUtf8Error> for OwError {
     fn from(_e: std::string::FromUtf8Error) -> Self {
-        OwError::Text("FromUTF8Error".into())
+        OwError::text("FromUTF8Error")
     }
 }
 impl From<std::ffi::NulError> for OwError {
     fn from(_e: std::ffi::NulError) -> Self {
-        OwError::Text("Nul Error".into())
+        OwError::text("Nul Error")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constructor_test() {
+        assert!(matches!(OwError::general("no"), OwError::General(e) if e == "no"));
+        assert!(matches!(OwError::input("bad".to_string()), OwError::Input(e) if e == "bad"));
+        assert!(
+            matches!(OwError::output(format!("{} bytes", 3)), OwError::Output(e) if e == "3 bytes")
+        );
+        assert!(matches!(OwError::numeric("x"), OwError::Numeric(e) if e == "x"));
+        assert!(matches!(OwError::text("y"), OwError::Text(e) if e == "y"));
+        assert_eq!(OwError::input("bad").to_string(), "Input error: bad");
+    }
+
+    #[test]
+    fn io_kind_test() {
+        let e: OwError = io::Error::from(io::ErrorKind::TimedOut).into();
+        assert_eq!(e.io_kind(), Some(io::ErrorKind::TimedOut));
+        assert_eq!(OwError::general("no").io_kind(), None);
+    }
+
+    #[test]
+    fn server_test() {
+        let codes = [
+            (-2, "owserver: No such file or directory"),
+            (-5, "owserver: Input/output error"),
+            (-22, "owserver: Invalid argument"),
+            (-42, "owserver: No message of desired type"),
+            (-1000, "owserver: Unknown error -1000"),
+        ];
+        for (code, text) in codes {
+            assert_eq!(OwError::server(code).to_string(), text);
+        }
+        assert!(matches!(
+            OwError::server(-19),
+            OwError::Server {
+                code: -19,
+                meaning: "No such device"
+            }
+        ));
     }
 }
@@ -37,8 +37,10 @@
 // {c} 2025 Paul H Alfille
 
 pub mod message;
+pub use crate::message::device_io;
 pub use crate::message::parse_args;
-pub use crate::message::{new, OwMessage};
+pub use crate::message::tree;
+pub use crate::message::{new, FlagBits, IntoPath, OwMessage};
 
 pub mod error;
 pub use error::{OwEResult, OwError};
@@ -46,6 +48,8 @@ pub use error::{OwEResult, OwError};
 pub mod console;
 pub use console::{console_line, console_lines};
 
+pub mod logging;
+
 pub mod bus_list;
 pub mod bus_thread;
 pub mod ds9097e;
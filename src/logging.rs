@@ -0,0 +1,107 @@
+//! ### logging module
+//! Diagnostics go through the `log` crate facade
+//! * library users can route them to `env_logger`, `tracing`, ...
+//! * CLI programs get a minimal stderr logger from the _-d_ option
+//!   * -d shows debug records
+//!   * -d -d also shows trace records
+//! * does nothing if a logger was already installed
+
+// owrust project
+// https://github.com/alfille/owrust
+//
+// This is a Rust version of my C owfs code for talking to 1-wire devices via owserver
+// Basically owserver can talk to the physical devices, and provides network access via my "owserver protocol"
+//
+// MIT Licence
+// {c} 2025 Paul H Alfille
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Minimal logger writing to stderr
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("{} {}", record.level(), record.args());
+            #[cfg(test)]
+            tests::keep(record);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: StderrLogger = StderrLogger;
+
+/// ### level_filter
+/// Log level for a count of _-d_ options
+pub fn level_filter(debug: u32) -> LevelFilter {
+    match debug {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+/// ### debug_level
+/// Set the log level from the count of _-d_ options
+/// * installs the stderr logger unless another logger is present
+pub fn debug_level(debug: u32) {
+    // not while a test is checking log records
+    #[cfg(test)]
+    let _serial = tests::serial();
+    // An existing logger (e.g. from a library user) is kept
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(level_filter(debug));
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::{Mutex, MutexGuard};
+
+    // the logger and level are process-wide: tests that check records take turns
+    static SERIAL: Mutex<()> = Mutex::new(());
+    static RECORDS: Mutex<Vec<(log::Level, String)>> = Mutex::new(Vec::new());
+
+    pub(super) fn serial() -> MutexGuard<'static, ()> {
+        SERIAL.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub(super) fn keep(record: &Record) {
+        if let Ok(mut records) = RECORDS.lock() {
+            records.push((record.level(), record.args().to_string()));
+        }
+    }
+
+    // start recording every level (until the guard is dropped)
+    pub(crate) fn capture() -> MutexGuard<'static, ()> {
+        let guard = serial();
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(LevelFilter::Trace);
+        RECORDS.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        guard
+    }
+
+    // was a record at this level logged that matches?
+    pub(crate) fn logged(level: log::Level, matches: impl Fn(&str) -> bool) -> bool {
+        RECORDS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .any(|(l, text)| *l == level && matches(text))
+    }
+
+    #[test]
+    fn level_test() {
+        assert_eq!(level_filter(0), LevelFilter::Warn);
+        assert_eq!(level_filter(1), LevelFilter::Debug);
+        assert_eq!(level_filter(2), LevelFilter::Trace);
+        assert_eq!(level_filter(5), LevelFilter::Trace);
+    }
+}
@@ -37,8 +37,13 @@
 // {c} 2025 Paul H Alfille
 
 use ::std::thread;
-use std::net::TcpListener;
+use std::collections::{HashMap, VecDeque};
+use std::io::Read;
+use std::net::{TcpListener, TcpStream};
 use std::str;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 mod response;
 use response::OwResponse;
@@ -47,23 +52,83 @@ mod query;
 use query::OwQuery;
 
 mod server;
-use server::OwServerInstance;
+use server::{OwServerInstance, RelayTokens};
 
 pub mod stream;
 use stream::Stream;
 
+pub mod timing;
+use timing::Timing;
+
+pub mod builder;
+use builder::OwMessageBuilder;
+
+pub mod server_info;
+use server_info::ServerInfo;
+
+pub mod self_test;
+use self_test::SelfTest;
+
+pub mod config;
+
+pub mod poll;
+
+pub mod tree;
+use poll::Poll;
+
+pub mod device_io;
+
+use crate::rom_id::RomId;
+
+mod cache;
+use cache::TtlCache;
+use device_io::{DeviceReader, DeviceWriter};
+
+#[cfg(feature = "mdns")]
+pub mod discover;
+
 pub use crate::error::{OwEResult, OwError};
 
 pub mod parse_args;
 pub mod print_message;
+use print_message::PrintMessage;
 
 /// Type for server tokens to prevent owserver network loops
 pub type Token = [u8; 16];
 const SERVERMESSAGE: u32 = 1 << 16;
 const SERVERTOKENS: u32 = 0xFFFF;
+// Largest payload accepted from the network (guards against a bad header)
+// * far above any real value or directory packet
+const MAX_PAYLOAD: i32 = 1 << 20;
+// protocol version sits above the server message bit and token count
+const SERVERPROTOCOL_SHIFT: u32 = 17;
+const MAX_PROTOCOL: u32 = u32::MAX >> SERVERPROTOCOL_SHIFT;
 mod token;
 use token::make_token;
 
+/// ### IntoPath
+/// 1-wire path argument of the OwMessage methods
+/// * accepts `&str`, `String` or `&String` alike
+/// * a `String` is moved without copying
+pub trait IntoPath {
+    fn into_path(self) -> String;
+}
+impl IntoPath for &str {
+    fn into_path(self) -> String {
+        self.to_string()
+    }
+}
+impl IntoPath for String {
+    fn into_path(self) -> String {
+        self
+    }
+}
+impl IntoPath for &String {
+    fn into_path(self) -> String {
+        self.clone()
+    }
+}
+
 /// ### new
 /// Creates a new OwMessage
 /// * configure flags and server address before using
@@ -73,6 +138,85 @@ pub fn new() -> OwMessage {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+/// ### Device address case
+/// case of the hex digits in device addresses sent to owserver
+/// * property names are never changed
+pub enum AddressCase {
+    UNCHANGED,
+    UPPER,
+    LOWER,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+/// ### Color output
+/// when to color program output (e.g. **owtree**)
+/// * AUTO colors only when writing to a terminal
+pub enum Color {
+    AUTO,
+    ALWAYS,
+    NEVER,
+}
+
+/// ### Color from a name
+/// * auto always never
+/// * unknown names are an Input error
+impl TryFrom<&str> for Color {
+    type Error = OwError;
+    fn try_from(name: &str) -> OwEResult<Self> {
+        match name.to_lowercase().as_str() {
+            "auto" => Ok(Color::AUTO),
+            "always" => Ok(Color::ALWAYS),
+            "never" => Ok(Color::NEVER),
+            _ => Err(OwError::Input(format!(
+                "Unknown color choice {} (auto, always or never)",
+                name
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+/// ### GetResult
+/// what **get** found at a path
+/// * Value -- file contents (as **read**)
+/// * Dir -- directory entries (as **dirall**)
+pub enum GetResult {
+    Value(Vec<u8>),
+    Dir(Vec<String>),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+/// ### Tree drawing style
+/// lines drawn between entries by **owtree**
+/// * UNICODE box-drawing characters (default)
+/// * ASCII for terminals without them
+/// * INDENT plain spaces, no lines
+pub enum TreeStyle {
+    UNICODE,
+    ASCII,
+    INDENT,
+}
+
+/// ### TreeStyle from a name
+/// * unicode ascii indent
+/// * unknown names are an Input error
+impl TryFrom<&str> for TreeStyle {
+    type Error = OwError;
+    fn try_from(name: &str) -> OwEResult<Self> {
+        match name.to_lowercase().as_str() {
+            "unicode" => Ok(TreeStyle::UNICODE),
+            "ascii" => Ok(TreeStyle::ASCII),
+            "indent" => Ok(TreeStyle::INDENT),
+            _ => Err(OwError::Input(format!(
+                "Unknown tree style {} (ascii, unicode or indent)",
+                name
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// ### Temperature scale
 /// sent to owserver in the flag parameter since only the original 1-wire
 /// program in the chain knows the type of value being sought
@@ -85,6 +229,7 @@ pub enum Temperature {
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// ### Pressure scale
 /// sent to owserver in the flag parameter since only the original 1-wire
 /// program in the chain knows the type of value being sought
@@ -98,7 +243,75 @@ pub enum Pressure {
     DEFAULT,
 }
 
+/// ### Temperature from a scale name
+/// * celsius c fahrenheit f kelvin k rankine r
+/// * case-insensitive
+/// * unknown names are an Input error
+impl TryFrom<&str> for Temperature {
+    type Error = OwError;
+    fn try_from(name: &str) -> OwEResult<Self> {
+        match name.to_lowercase().as_str() {
+            "celsius" | "c" => Ok(Temperature::CELSIUS),
+            "fahrenheit" | "farenheit" | "f" => Ok(Temperature::FARENHEIT),
+            "kelvin" | "k" => Ok(Temperature::KELVIN),
+            "rankine" | "r" => Ok(Temperature::RANKINE),
+            _ => Err(OwError::Input(format!(
+                "Unknown temperature scale {}",
+                name
+            ))),
+        }
+    }
+}
+
+impl Temperature {
+    /// ### symbol
+    /// unit shown after a reading (e.g. °F)
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Temperature::CELSIUS | Temperature::DEFAULT => "°C",
+            Temperature::FARENHEIT => "°F",
+            Temperature::KELVIN => "K",
+            Temperature::RANKINE => "°R",
+        }
+    }
+}
+
+/// ### Pressure from a scale name
+/// * mmhg inhg mbar atm pa psi
+/// * case-insensitive
+/// * unknown names are an Input error
+impl TryFrom<&str> for Pressure {
+    type Error = OwError;
+    fn try_from(name: &str) -> OwEResult<Self> {
+        match name.to_lowercase().as_str() {
+            "mmhg" => Ok(Pressure::MMHG),
+            "inhg" => Ok(Pressure::INHG),
+            "mbar" => Ok(Pressure::MBAR),
+            "atm" => Ok(Pressure::ATM),
+            "pa" => Ok(Pressure::PA),
+            "psi" => Ok(Pressure::PSI),
+            _ => Err(OwError::Input(format!("Unknown pressure scale {}", name))),
+        }
+    }
+}
+
+impl Pressure {
+    /// ### symbol
+    /// unit shown after a reading (e.g. inHg)
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Pressure::MBAR | Pressure::DEFAULT => "mbar",
+            Pressure::MMHG => "mmHg",
+            Pressure::INHG => "inHg",
+            Pressure::ATM => "atm",
+            Pressure::PA => "Pa",
+            Pressure::PSI => "psi",
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// ### 1-wire ID format
 /// has components:
 ///  F family code (1 byte)
@@ -114,6 +327,54 @@ pub enum Format {
     DEFAULT,
 }
 
+/// ### FlagBits
+/// owserver flag word bits for a setting
+/// * implemented by Temperature, Pressure and Format
+/// * **make_flags** combines them
+pub trait FlagBits {
+    fn bits(&self) -> u32;
+}
+
+impl FlagBits for Temperature {
+    fn bits(&self) -> u32 {
+        match self {
+            Temperature::CELSIUS => OwMessage::TEMPERATURE_C,
+            Temperature::FARENHEIT => OwMessage::TEMPERATURE_F,
+            Temperature::KELVIN => OwMessage::TEMPERATURE_K,
+            Temperature::RANKINE => OwMessage::TEMPERATURE_R,
+            Temperature::DEFAULT => OwMessage::TEMPERATURE_C,
+        }
+    }
+}
+
+impl FlagBits for Pressure {
+    fn bits(&self) -> u32 {
+        match self {
+            Pressure::MBAR => OwMessage::PRESSURE_MBAR,
+            Pressure::MMHG => OwMessage::PRESSURE_MMHG,
+            Pressure::INHG => OwMessage::PRESSURE_INHG,
+            Pressure::ATM => OwMessage::PRESSURE_ATM,
+            Pressure::PA => OwMessage::PRESSURE_PA,
+            Pressure::PSI => OwMessage::PRESSURE_PSI,
+            Pressure::DEFAULT => OwMessage::PRESSURE_MBAR,
+        }
+    }
+}
+
+impl FlagBits for Format {
+    fn bits(&self) -> u32 {
+        match self {
+            Format::FI => OwMessage::FORMAT_FI,
+            Format::FdI => OwMessage::FORMAT_F_I,
+            Format::FIC => OwMessage::FORMAT_FIC,
+            Format::FIdC => OwMessage::FORMAT_FI_C,
+            Format::FdIC => OwMessage::FORMAT_F_IC,
+            Format::FdIdC => OwMessage::FORMAT_F_I_C,
+            Format::DEFAULT => OwMessage::FORMAT_F_I,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 /// ### OwMessage
 /// structure that manages the connection to owserver
@@ -129,6 +390,9 @@ pub enum Format {
 /// ```
 pub struct OwMessage {
     listener: Option<String>,
+    workers: usize,
+    heartbeat: Option<Duration>,
+    cancel: Option<Arc<AtomicBool>>,
     token: Token,
     temperature: Temperature,
     pressure: Pressure,
@@ -137,10 +401,44 @@ pub struct OwMessage {
     offset: u32,
     slash: bool,
     hex: bool,
+    human: bool,
+    trim: bool,
+    units: bool,
+    quiet: bool,
+    color: Color,
+    tree_style: TreeStyle,
+    raw: bool,
+    strict: bool,
+    uncached: bool,
+    address_case: AddressCase,
     bare: bool,
+    no_bus: bool,
     prune: bool,
+    first_only: bool,
+    dry_run: bool,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    null: bool,
+    columns: usize,
+    csv: bool,
+    timestamp: bool,
+    epoch: bool,
+    interval: Option<Duration>,
+    min_interval: Duration,
+    last_query: Option<Instant>,
+    dir_cache: TtlCache<(String, [u32; 2]), Vec<u8>>,
+    read_cache: TtlCache<(String, [u32; 3]), Vec<u8>>,
+    align: bool,
     stream: Stream,
     debug: u32,
+    profile: bool,
+    timing: Timing,
+    server_version: Option<u32>,
+    conversion: Duration,
+    user_flags: u32,
+    write_nonces: VecDeque<u64>,
+    max_packets: usize,
+    send_version: u32,
     flags: u32,
 }
 
@@ -167,7 +465,7 @@ impl OwMessage {
     const PRESSURE_INHG: u32 = 0x000C0000;
     const PRESSURE_PSI: u32 = 0x00100000;
     const PRESSURE_PA: u32 = 0x00140000;
-    const PRESSURE_MASK: u32 = 0x001F0000;
+    const PRESSURE_MASK: u32 = 0x001C0000;
     // -- Other independent flags
     #[allow(unused)]
     const OWNET_FLAG: u32 = 0x00000100;
@@ -186,9 +484,34 @@ impl OwMessage {
     #[allow(unused)]
     const BUS_RET: u32 = 0x00000002;
 
+    /// Flag bits computed by **make_flags** from the configuration
+    /// * format, temperature and pressure scales, persistence and bus entries
+    /// * all other bits are free for **set_flag** / **clear_flag**
+    pub const MANAGED_FLAGS: u32 = OwMessage::FORMAT_MASK
+        | OwMessage::TEMPERATURE_MASK
+        | OwMessage::PRESSURE_MASK
+        | OwMessage::PERSISTENCE
+        | OwMessage::BUS_RET;
+
+    // How many recent write nonces are remembered by write_once
+    const WRITE_NONCES: usize = 64;
+
+    // Default cap on directory continuation packets (guards a misbehaving owserver)
+    const MAX_PACKETS: usize = 10000;
+
+    // owserver return code for a missing path (-ENOENT)
+    const NOT_PRESENT: i32 = -2;
+
+    // Device families that join a simultaneous temperature conversion
+    const TEMPERATURE_FAMILIES: [u8; 5] = [0x10, 0x22, 0x28, 0x3B, 0x42];
+
+    // Longest (12 bit) temperature conversion plus margin
+    const CONVERSION_TIME: Duration = Duration::from_millis(800);
+
     /// ### flag_string
     /// Create a 1-line summary of the owserver message flags in a message
-    /// * Temerature (C|K|F|R)
+    /// * only the settings in effect, space separated (e.g. `C mbar f.i net alias bus`)
+    /// * Temperature (C|K|F|R)
     /// * Pressure (mbar | mmHg | inHg | pa | atm | psi )
     /// * OwNet (net)
     /// * Uncached (uncache)
@@ -196,61 +519,104 @@ impl OwMessage {
     /// * Alias (alias)
     /// * Bus_Ret (bus)
     pub fn flag_string(flag: u32) -> String {
+        OwMessage::flag_fields(flag)
+            .iter()
+            .map(|(_, value)| *value)
+            .filter(|value| !value.is_empty())
+            .collect::<Vec<&str>>()
+            .join(" ")
+            .to_string()
+    }
+
+    /// ### flag_fields
+    /// Decode the owserver message flags into (field, value) pairs
+    /// * temperature, pressure, format -- the scale or format in use
+    /// * ownet, uncached, safemode, alias, persist, bus_ret -- the word if set, else empty
+    /// * used by **flag_string** and the _--show-flags_ option
+    pub fn flag_fields(flag: u32) -> [(&'static str, &'static str); 9] {
         [
-            match flag & OwMessage::TEMPERATURE_MASK {
-                OwMessage::TEMPERATURE_C => "C",
-                OwMessage::TEMPERATURE_F => "F",
-                OwMessage::TEMPERATURE_K => "K",
-                _ => "R",
-            },
-            match flag & OwMessage::PRESSURE_MASK {
-                OwMessage::PRESSURE_MBAR => "mbar",
-                OwMessage::PRESSURE_MMHG => "mmHg",
-                OwMessage::PRESSURE_INHG => "inHg",
-                OwMessage::PRESSURE_PA => "pa",
-                OwMessage::PRESSURE_ATM => "atm",
-                _ => "psi",
-            },
-            match flag & OwMessage::FORMAT_MASK {
-                OwMessage::FORMAT_F_I => "f.i",
-                OwMessage::FORMAT_FI => "fi",
-                OwMessage::FORMAT_F_I_C => "f.i.c",
-                OwMessage::FORMAT_F_IC => "f.ic",
-                OwMessage::FORMAT_FI_C => "fi.c",
-                _ => "fic",
-            },
-            match flag & OwMessage::OWNET_FLAG {
-                0 => "",
-                _ => "net",
-            },
-            match flag & OwMessage::UNCACHED {
-                0 => "",
-                _ => "uncache",
-            },
-            match flag & OwMessage::SAFEMODE {
-                0 => "",
-                _ => "safe",
-            },
-            match flag & OwMessage::ALIAS {
-                0 => "",
-                _ => "alias",
-            },
-            match flag & OwMessage::PERSISTENCE {
-                0 => "",
-                _ => "persist",
-            },
-            match flag & OwMessage::BUS_RET {
-                0 => "",
-                _ => "bus",
-            },
+            (
+                "temperature",
+                match flag & OwMessage::TEMPERATURE_MASK {
+                    OwMessage::TEMPERATURE_C => "C",
+                    OwMessage::TEMPERATURE_F => "F",
+                    OwMessage::TEMPERATURE_K => "K",
+                    _ => "R",
+                },
+            ),
+            (
+                "pressure",
+                match flag & OwMessage::PRESSURE_MASK {
+                    OwMessage::PRESSURE_MBAR => "mbar",
+                    OwMessage::PRESSURE_MMHG => "mmHg",
+                    OwMessage::PRESSURE_INHG => "inHg",
+                    OwMessage::PRESSURE_PA => "pa",
+                    OwMessage::PRESSURE_ATM => "atm",
+                    _ => "psi",
+                },
+            ),
+            (
+                "format",
+                match flag & OwMessage::FORMAT_MASK {
+                    OwMessage::FORMAT_F_I => "f.i",
+                    OwMessage::FORMAT_FI => "fi",
+                    OwMessage::FORMAT_F_I_C => "f.i.c",
+                    OwMessage::FORMAT_F_IC => "f.ic",
+                    OwMessage::FORMAT_FI_C => "fi.c",
+                    _ => "fic",
+                },
+            ),
+            (
+                "ownet",
+                match flag & OwMessage::OWNET_FLAG {
+                    0 => "",
+                    _ => "net",
+                },
+            ),
+            (
+                "uncached",
+                match flag & OwMessage::UNCACHED {
+                    0 => "",
+                    _ => "uncache",
+                },
+            ),
+            (
+                "safemode",
+                match flag & OwMessage::SAFEMODE {
+                    0 => "",
+                    _ => "safe",
+                },
+            ),
+            (
+                "alias",
+                match flag & OwMessage::ALIAS {
+                    0 => "",
+                    _ => "alias",
+                },
+            ),
+            (
+                "persist",
+                match flag & OwMessage::PERSISTENCE {
+                    0 => "",
+                    _ => "persist",
+                },
+            ),
+            (
+                "bus_ret",
+                match flag & OwMessage::BUS_RET {
+                    0 => "",
+                    _ => "bus",
+                },
+            ),
         ]
-        .join(" ")
-        .to_string()
     }
 
     fn new() -> Self {
         let mut owc = OwMessage {
             listener: None,
+            workers: 0,
+            heartbeat: None,
+            cancel: None,
             token: make_token(),
             temperature: Temperature::DEFAULT,
             pressure: Pressure::DEFAULT,
@@ -259,103 +625,390 @@ impl OwMessage {
             offset: 0,
             slash: false,
             hex: false,
+            human: false,
+            trim: false,
+            units: false,
+            quiet: false,
+            color: Color::AUTO,
+            tree_style: TreeStyle::UNICODE,
+            raw: false,
+            strict: false,
+            uncached: false,
+            address_case: AddressCase::UNCHANGED,
             bare: false,
+            no_bus: false,
             prune: false,
+            first_only: false,
+            dry_run: false,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            null: false,
+            columns: 0,
+            csv: false,
+            timestamp: false,
+            epoch: false,
+            interval: None,
+            min_interval: Duration::ZERO,
+            last_query: None,
+            dir_cache: TtlCache::new(),
+            read_cache: TtlCache::new(),
+            align: false,
             stream: Stream::new(),
             debug: 0,
+            profile: false,
+            timing: Timing::default(),
+            server_version: None,
+            conversion: OwMessage::CONVERSION_TIME,
+            user_flags: 0,
+            write_nonces: VecDeque::new(),
+            max_packets: OwMessage::MAX_PACKETS,
+            send_version: OwQuery::SENDVERSION,
             flags: 0,
         };
         owc.make_flags();
         owc
     }
 
+    /// ### builder
+    /// Configure an OwMessage from library code (no command line needed)
+    /// ```
+    /// use owrust::message::{OwMessage, Pressure};
+    /// let owserver = OwMessage::builder()
+    ///     .server("localhost:4304")
+    ///     .pressure(Pressure::PSI)
+    ///     .build();
+    /// ```
+    pub fn builder() -> OwMessageBuilder {
+        OwMessageBuilder::new()
+    }
+
     // make the owserver flag field based on configuration settings
     pub fn make_flags(&mut self) {
         let mut flags = 0;
-        if !self.bare {
+        if !self.no_bus {
             flags |= OwMessage::BUS_RET;
         }
         if self.stream.get_persistence() {
             flags |= OwMessage::PERSISTENCE;
         }
-        flags |= match self.temperature {
-            Temperature::CELSIUS => OwMessage::TEMPERATURE_C,
-            Temperature::FARENHEIT => OwMessage::TEMPERATURE_F,
-            Temperature::KELVIN => OwMessage::TEMPERATURE_K,
-            Temperature::RANKINE => OwMessage::TEMPERATURE_R,
-            Temperature::DEFAULT => OwMessage::TEMPERATURE_C,
-        };
+        flags |= self.temperature.bits();
+        flags |= self.pressure.bits();
+        flags |= self.format.bits();
+        self.flags = flags | self.user_flags
+    }
 
-        flags |= match self.pressure {
-            Pressure::MBAR => OwMessage::PRESSURE_MBAR,
-            Pressure::MMHG => OwMessage::PRESSURE_MMHG,
-            Pressure::INHG => OwMessage::PRESSURE_INHG,
-            Pressure::ATM => OwMessage::PRESSURE_ATM,
-            Pressure::PA => OwMessage::PRESSURE_PA,
-            Pressure::PSI => OwMessage::PRESSURE_PSI,
-            Pressure::DEFAULT => OwMessage::PRESSURE_MBAR,
-        };
+    /// ### set_flag
+    /// set raw bits in the owserver flag word
+    /// * for owserver features not otherwise modeled
+    /// * free bits (outside **MANAGED_FLAGS**) survive **make_flags**
+    /// * managed bits are recomputed by the next **make_flags**
+    pub fn set_flag(&mut self, bit: u32) {
+        self.user_flags |= bit & !OwMessage::MANAGED_FLAGS;
+        self.flags |= bit;
+    }
 
-        flags |= match self.format {
-            Format::FI => OwMessage::FORMAT_FI,
-            Format::FdI => OwMessage::FORMAT_F_I,
-            Format::FIC => OwMessage::FORMAT_FIC,
-            Format::FIdC => OwMessage::FORMAT_FI_C,
-            Format::FdIC => OwMessage::FORMAT_F_IC,
-            Format::FdIdC => OwMessage::FORMAT_F_I_C,
-            Format::DEFAULT => OwMessage::FORMAT_F_I,
-        };
-        self.flags = flags
+    /// ### clear_flag
+    /// clear raw bits in the owserver flag word
+    /// * undoes **set_flag**
+    /// * managed bits are recomputed by the next **make_flags**
+    pub fn clear_flag(&mut self, bit: u32) {
+        self.user_flags &= !bit;
+        self.flags &= !bit;
+    }
+
+    /// ### flags
+    /// current owserver flag word
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    // All queries are built here so path rules apply to every message type
+    fn query(&self, mtype: u32, text: &str, value: Option<&[u8]>) -> OwEResult<OwQuery> {
+        let path = self.query_path(text);
+        let mut msg = OwQuery::new(self.flags, mtype, Some(&path), value, self.token)?;
+        msg.set_protocol(self.send_version);
+        Ok(msg)
+    }
+
+    // path as sent to owserver
+    // * _--upcase_ or _--downcase_ sets the case of device addresses
+    // * _--via-uncached_ puts the path under /uncached (unless already there)
+    fn query_path(&self, text: &str) -> String {
+        let cased = OwMessage::address_case(text, &self.address_case);
+        let text = cased.as_str();
+        if !self.uncached {
+            return text.to_string();
+        }
+        let trimmed = text.trim_start_matches('/');
+        if trimmed == "uncached" || trimmed.starts_with("uncached/") {
+            return text.to_string();
+        }
+        match trimmed.is_empty() {
+            true => "/uncached".to_string(),
+            _ => format!("/uncached/{}", trimmed),
+        }
     }
 
+    // WRITE starts at the _--offset_ position
     fn make_write(&self, text: &str, value: &[u8]) -> OwEResult<OwQuery> {
-        OwQuery::new(
-            self.flags,
-            OwQuery::WRITE,
-            Some(text),
-            Some(value),
-            self.token,
-        )
+        let mut msg = self.query(OwQuery::WRITE, text, Some(value))?;
+        msg.offset = self.offset;
+        Ok(msg)
     }
+    // READ asks for the _--size_ bytes (0 leaves the owserver default) from _--offset_
     fn make_read(&self, text: &str) -> OwEResult<OwQuery> {
-        OwQuery::new(self.flags, OwQuery::READ, Some(text), None, self.token)
+        let mut msg = self.query(OwQuery::READ, text, None)?;
+        if self.size != 0 {
+            msg.size = self.size;
+        }
+        msg.offset = self.offset;
+        Ok(msg)
     }
     fn make_dir(&self, text: &str) -> OwEResult<OwQuery> {
-        OwQuery::new(self.flags, OwQuery::DIR, Some(text), None, self.token)
+        self.query(OwQuery::DIR, text, None)
     }
+    // SIZE and PRESENT carry no data, so size and offset are 0
     fn make_size(&self, text: &str) -> OwEResult<OwQuery> {
-        OwQuery::new(self.flags, OwQuery::SIZE, Some(text), None, self.token)
+        let mut msg = self.query(OwQuery::SIZE, text, None)?;
+        msg.size = 0;
+        msg.offset = 0;
+        Ok(msg)
     }
     fn make_present(&self, text: &str) -> OwEResult<OwQuery> {
-        OwQuery::new(self.flags, OwQuery::PRESENT, Some(text), None, self.token)
+        let mut msg = self.query(OwQuery::PRESENT, text, None)?;
+        msg.size = 0;
+        msg.offset = 0;
+        Ok(msg)
+    }
+    // NOP has no path
+    fn make_nop(&self) -> OwEResult<OwQuery> {
+        let mut msg = OwQuery::new(self.flags, OwQuery::NOP, None, None, self.token)?;
+        msg.set_protocol(self.send_version);
+        Ok(msg)
     }
     fn make_dirall(&self, text: &str) -> OwEResult<OwQuery> {
-        OwQuery::new(self.flags, OwQuery::DIRALL, Some(text), None, self.token)
+        self.query(OwQuery::DIRALL, text, None)
     }
     fn make_get(&self, text: &str) -> OwEResult<OwQuery> {
-        OwQuery::new(self.flags, OwQuery::GET, Some(text), None, self.token)
+        self.query(OwQuery::GET, text, None)
     }
     fn make_dirallslash(&self, text: &str) -> OwEResult<OwQuery> {
-        OwQuery::new(
-            self.flags,
-            OwQuery::DIRALLSLASH,
-            Some(text),
-            None,
-            self.token,
-        )
+        self.query(OwQuery::DIRALLSLASH, text, None)
     }
     fn make_getslash(&self, text: &str) -> OwEResult<OwQuery> {
-        OwQuery::new(self.flags, OwQuery::GETSLASH, Some(text), None, self.token)
+        self.query(OwQuery::GETSLASH, text, None)
+    }
+
+    fn send_get_single(&mut self, send: OwQuery) -> OwEResult<OwResponse> {
+        let rcv = self.exchange(send, false);
+        self.show_profile();
+        rcv
+    }
+
+    fn send_get_many(&mut self, send: OwQuery) -> OwEResult<OwResponse> {
+        let rcv = self.exchange(send, true);
+        self.show_profile();
+        rcv
+    }
+
+    // one query and its whole reply
+    // * a reused (persistent or pooled) connection that fails before any reply
+    //   is replaced by a new connection, once
+    // * the connection is only kept after a complete exchange, else shut down
+    fn exchange(&mut self, mut send: OwQuery, many: bool) -> OwEResult<OwResponse> {
+        let mut first = self
+            .send_packet(&mut send)
+            .and_then(|_| self.get_msg_single());
+        if first.is_err() && self.stream.reused() {
+            log::debug!("Stale owserver connection, reconnecting");
+            first = self
+                .stream
+                .connect_new()
+                .and_then(|_| self.send_packet(&mut send))
+                .and_then(|_| self.get_msg_single());
+        }
+        let rcv = match (first, many) {
+            (Ok(rcv), true) => self.get_msg_more(rcv),
+            (first, _) => first,
+        };
+        match rcv {
+            Ok(_) => self.stream.finish(),
+            Err(_) => self.stream.discard(),
+        }
+        rcv
+    }
+
+    // print phase timing if --profile
+    fn show_profile(&self) {
+        if self.profile {
+            eprintln!("{}", self.timing.summary());
+        }
+    }
+
+    /// ### close
+    /// Shut down the owserver connection
+    /// * useful with _--persist_ to free the owserver connection promptly
+    ///   * returned to the shared pool instead if the OwMessage was built with **pool**
+    /// * the next operation reconnects transparently
+    pub fn close(&mut self) {
+        self.stream.close();
+    }
+
+    /// ### connect
+    /// Connect to owserver now rather than at the first query
+    /// * e.g. to fail fast at program start if owserver is unreachable
+    /// * tries each _--server_ target in turn
+    /// * returns () or connection error
+    pub fn connect(&mut self) -> OwEResult<()> {
+        self.stream.connect()
+    }
+
+    /// ### is_connected
+    /// is there an open owserver connection?
+    /// * without _--persist_ each query makes its own connection
+    pub fn is_connected(&self) -> bool {
+        self.stream.is_connected()
     }
 
-    fn send_get_single(&mut self, mut send: OwQuery) -> OwEResult<OwResponse> {
-        self.send_packet(&mut send)?;
-        self.get_msg_single()
+    /// ### reconnect
+    /// Drop the owserver connection and connect again
+    /// * for callers who know the connection is stale
+    /// * returns () or connection error
+    pub fn reconnect(&mut self) -> OwEResult<()> {
+        self.stream.close();
+        self.stream.connect()
     }
 
-    fn send_get_many(&mut self, mut send: OwQuery) -> OwEResult<OwResponse> {
-        self.send_packet(&mut send)?;
-        self.get_msg_many()
+    /// ### server_version
+    /// protocol version word from the last owserver response
+    /// * None until a response is received
+    /// * upper 16 bits are the protocol version, lower 16 bits the token count
+    pub fn server_version(&self) -> Option<u32> {
+        self.server_version
+    }
+
+    /// ### server_build
+    /// owserver program version (from `/system/process/version`)
+    /// * useful to include in bug reports
+    /// * returns `String` or error
+    pub fn server_build(&mut self) -> OwEResult<String> {
+        let v = self.read("/system/process/version")?;
+        Ok(String::from_utf8_lossy(&v).trim().to_string())
+    }
+
+    /// ### server_info
+    /// one-call summary of the owserver
+    /// * process id from `/system/process/pid`
+    /// * count of devices in the root directory
+    /// * protocol version from the last response (see **server_version**)
+    /// * returns `ServerInfo` or error
+    pub fn server_info(&mut self) -> OwEResult<ServerInfo> {
+        let v = self.read("/system/process/pid")?;
+        let text = String::from_utf8_lossy(&v);
+        let pid: u32 = text
+            .trim()
+            .parse()
+            .map_err(|_| OwError::Numeric(format!("{} from /system/process/pid", text.trim())))?;
+        let devices = self
+            .dirall("/")?
+            .iter()
+            .filter(|d| OwMessage::is_address(OwMessage::leaf_name(d)))
+            .count();
+        Ok(ServerInfo {
+            version: self.server_version(),
+            pid,
+            devices,
+        })
+    }
+
+    /// ### ping
+    /// check that owserver answers at all
+    /// * sends a NOP message (no 1-wire bus traffic)
+    /// * returns () or error
+    pub fn ping(&mut self) -> OwEResult<()> {
+        let msg = self.make_nop()?;
+        let rcv = self.send_get_single(msg)?;
+        if rcv.ret == 0 {
+            Ok(())
+        } else {
+            Err(OwError::server(rcv.ret))
+        }
+    }
+
+    /// ### self_test
+    /// quick health check of the owserver setup
+    /// * ping, root directory, program version and a present check
+    /// * each check is tried even if an earlier one failed
+    /// * returns a `SelfTest` report of what worked (see its **healthy**)
+    pub fn self_test(&mut self) -> OwEResult<SelfTest> {
+        let mut report = SelfTest::default();
+        match self.ping() {
+            Ok(()) => report.ping = true,
+            Err(e) => report.problems.push(format!("ping: {}", e)),
+        }
+        match self.dirall("/") {
+            Ok(d) if d.iter().any(|e| !e.is_empty()) => report.root_dir = true,
+            Ok(_) => report.problems.push("root directory: empty".to_string()),
+            Err(e) => report.problems.push(format!("root directory: {}", e)),
+        }
+        match self.server_build() {
+            Ok(v) if !v.is_empty() => report.version = true,
+            Ok(_) => report.problems.push("version: no value".to_string()),
+            Err(e) => report.problems.push(format!("version: {}", e)),
+        }
+        match self.present("/") {
+            Ok(true) => report.present = true,
+            Ok(false) => report.problems.push("present: / not present".to_string()),
+            Err(e) => report.problems.push(format!("present: {}", e)),
+        }
+        Ok(report)
+    }
+
+    /// ### set_read_size
+    /// Number of bytes requested by each read (the _--size_ option)
+    /// * 0 (default) requests the owserver maximum (65536)
+    /// * a small size saves owserver work for short values like temperatures
+    pub fn set_read_size(&mut self, size: u32) {
+        self.size = size;
+    }
+
+    /// ### set_protocol_version
+    /// owserver protocol version sent with each query (default 0)
+    /// * for experimenting with (or pinning) owserver behavior
+    /// * 0 to 32767, otherwise an Input error
+    pub fn set_protocol_version(&mut self, version: u32) -> OwEResult<()> {
+        if version > MAX_PROTOCOL {
+            return Err(OwError::Input(format!(
+                "protocol version {} out of range (0-{})",
+                version, MAX_PROTOCOL
+            )));
+        }
+        self.send_version = version;
+        Ok(())
+    }
+
+    /// ### set_max_packets
+    /// Limit the continuation packets accepted for one directory listing
+    /// * default 10000
+    /// * exceeding the limit is an error rather than an endless loop
+    pub fn set_max_packets(&mut self, max_packets: usize) {
+        self.max_packets = max_packets;
+    }
+
+    /// ### take_stream
+    /// hands off the current owserver connection
+    /// * for custom protocol operations on the raw tcp stream
+    /// * returns None if not connected
+    /// * OwMessage will reconnect on next use
+    pub fn take_stream(&mut self) -> Option<TcpStream> {
+        self.stream.take()
+    }
+
+    /// ### timing
+    /// time spent in each protocol phase of the last operation
+    /// * connect, send and receive
+    pub fn timing(&self) -> &Timing {
+        &self.timing
     }
 
     // non-ping response
@@ -363,10 +1016,13 @@ impl OwMessage {
         let stream = match self.stream.get() {
             Some(s) => s,
             None => {
-                return Err(OwError::General("No Tcp stream defined".to_string()));
+                return Err(OwError::general("No Tcp stream defined"));
             }
         };
+        let start = Instant::now();
         let rcv = OwResponse::get(stream)?;
+        self.timing.add_receive(start.elapsed());
+        self.server_version = Some(rcv.version);
         Ok(rcv)
     }
 
@@ -375,27 +1031,30 @@ impl OwMessage {
         let stream = match self.stream.get() {
             Some(s) => s,
             None => {
-                return Err(OwError::General("No Tcp stream defined".to_string()));
+                return Err(OwError::general("No Tcp stream defined"));
             }
         };
         let rcv = OwResponse::get(stream)?;
+        self.server_version = Some(rcv.version);
         Ok(rcv)
     }
 
     // Loop through getting packets until payload empty
-    // for directories
-    fn get_msg_many(&mut self) -> OwEResult<OwResponse> {
-        let mut full_rcv = self.get_msg_single()?;
+    // for directories (first packet already read)
+    fn get_msg_more(&mut self, mut full_rcv: OwResponse) -> OwEResult<OwResponse> {
         if full_rcv.payload == 0 {
             return Ok(full_rcv);
         }
 
-        loop {
+        for _ in 0..self.max_packets {
+            if let Err(e) = self.check_cancel() {
+                // rest of the listing is still coming: connection can't be reused
+                self.stream.discard();
+                return Err(e);
+            }
             // get more packets and add content to first one, adjusting payload size
             let mut rcv = self.get_msg_single()?;
-            if self.debug > 0 {
-                eprintln!("Another packet");
-            }
+            log::trace!("Another packet");
             if rcv.payload == 0 {
                 return Ok(full_rcv);
             }
@@ -403,18 +1062,52 @@ impl OwMessage {
             full_rcv.content.append(&mut rcv.content); // add this packet's info
             full_rcv.payload += rcv.payload;
         }
+        Err(OwError::General(format!(
+            "Directory exceeded {} continuation packets",
+            self.max_packets
+        )))
     }
 
     fn send_packet(&mut self, msg: &mut OwQuery) -> OwEResult<()> {
+        self.pace();
+        self.timing = Timing::default();
+
         // Write to network
+        let start = Instant::now();
         self.stream.connect()?;
+        self.timing.connect = Some(start.elapsed());
+        log::debug!("Connected to owserver {}", self.stream.target());
+        self.stream.begin();
         let stream = match self.stream.get() {
             Some(s) => s,
             None => {
-                return Err(OwError::General("No Tcp stream defined".to_string()));
+                return Err(OwError::general("No Tcp stream defined"));
             }
         };
-        msg.send(stream)
+        let start = Instant::now();
+        msg.send(stream)?;
+        self.timing.send = Some(start.elapsed());
+        Ok(())
+    }
+
+    // _--min-interval_ spacing between queries (for slow buses)
+    fn pace(&mut self) {
+        if let Some(last) = self.last_query {
+            let elapsed = last.elapsed();
+            if elapsed < self.min_interval {
+                thread::sleep(self.min_interval - elapsed);
+            }
+        }
+        self.last_query = Some(Instant::now());
+    }
+
+    /// ### set_min_interval
+    /// Shortest time between queries (the _--min-interval_ option)
+    /// * spaces out **read_many**, **watch** and other repeated queries
+    /// * for slow or fragile 1-wire buses (e.g. many sensors on a DS2482)
+    /// * 0 (default) for no limit
+    pub fn set_min_interval(&mut self, min_interval: Duration) {
+        self.min_interval = min_interval;
     }
 
     fn get_value(
@@ -424,114 +1117,711 @@ impl OwMessage {
     ) -> OwEResult<Vec<u8>> {
         let msg = f(self, path)?;
         let rcv = self.send_get_single(msg)?;
-        if rcv.payload > 0 {
-            let v: Vec<u8> = rcv.content;
-            return Ok(v);
+        OwMessage::checked_value(rcv)
+    }
+
+    // content of a reply, or owserver's error (negative ret)
+    fn checked_value(rcv: OwResponse) -> OwEResult<Vec<u8>> {
+        match rcv.ret < 0 {
+            true => Err(OwError::server(rcv.ret)),
+            _ => Ok(OwMessage::value_of(rcv)),
+        }
+    }
+
+    // content of a reply (empty if none)
+    fn value_of(rcv: OwResponse) -> Vec<u8> {
+        match rcv.payload > 0 {
+            true => rcv.content,
+            _ => Vec::new(),
         }
-        Ok(Vec::new())
     }
 
     /// ### read
     /// reads a value from a 1-wire file
     /// * path is the 1-wire address of the file
     ///   * (e.g. /10.112233445566/temperature)
+    /// * honors the _--size_ and _--offset_ command line options
     /// * returns a `Vec<u8>` or error
+    ///   * an owserver error (e.g. no such path) is an error, not an empty value
     /// * result can be displayed with **show_result**
-    pub fn read(&mut self, path: &str) -> OwEResult<Vec<u8>> {
-        self.get_value(path, OwMessage::make_read)
-    }
-    /// ### write
-    /// write a value to a 1-wire file
-    /// * path is the 1-wire address of the file
-    /// * value is a `Vec<u8>` byte sequence to write
-    ///   * (e.g. /10.112233445566/temperature)
-    /// * returns () or error
-    pub fn write(&mut self, path: &str, value: &[u8]) -> OwEResult<()> {
-        let msg = OwMessage::make_write(self, path, value)?;
-        let rcv = self.send_get_single(msg)?;
-        if rcv.ret == 0 {
-            Ok(())
-        } else {
-            Err(OwError::Output(format!(
-                "Return code from owserver is error {}",
-                rcv.ret
-            )))
+    /// * answered from the cache if _--cache-ttl_ is set (see **set_cache_ttl**)
+    pub fn read(&mut self, path: impl IntoPath) -> OwEResult<Vec<u8>> {
+        let path = &path.into_path();
+        if !self.use_cache() {
+            return self.get_value(path, OwMessage::make_read);
         }
+        // same path read with other scales, size or offset is another value
+        let key = (path.clone(), [self.flags, self.size, self.offset]);
+        if let Some(value) = self.read_cache.get(&key) {
+            return Ok(value);
+        }
+        let msg = self.make_read(path)?;
+        let rcv = self.send_get_single(msg)?;
+        let value = OwMessage::checked_value(rcv)?;
+        self.read_cache.put(key, value.clone());
+        Ok(value)
     }
 
-    /// ### dirall
-    /// returns the path directory listing
-    /// * uses a separate message for each entry
-    /// * honors the _--dir_ command line option
-    /// * honors the _--bare_ command line option
-    /// * returns `Vec<u8>` or error
-    /// * result can be displayed with **show_text**
-    pub fn dir(&mut self, path: &str) -> OwEResult<Vec<String>> {
-        let msg = self.make_dir(path)?;
-        let mut rcv = self.send_get_many(msg)?;
-        self.dirboth(&mut rcv.content)
+    // client-side caches are skipped when reading past owserver's cache
+    // * _--via-uncached_ or the UNCACHED flag (e.g. **read_fresh**)
+    fn use_cache(&self) -> bool {
+        !self.uncached && self.flags & OwMessage::UNCACHED == 0
     }
 
-    /// ### present
-    /// returns the existence of a 1-wire device
-    /// * Rarely used function
-    /// * path is the 1-wire address of the the device
-    /// * returns bool or error
-    pub fn present(&mut self, path: &str) -> OwEResult<bool> {
-        let msg = self.make_present(path)?;
-        let rcv = self.send_get_single(msg)?;
-        Ok(rcv.ret == 0)
+    /// ### set_cache_ttl
+    /// Keep read values for reuse (the _--cache-ttl_ option)
+    /// * **read** answers from the cache while a value is younger than ttl
+    /// * a write to the path drops its cached value
+    /// * owserver errors are not cached
+    /// * None (default) for no caching
+    /// * ignored with _--via-uncached_ and by **read_fresh**
+    pub fn set_cache_ttl(&mut self, ttl: Option<Duration>) {
+        self.read_cache.set_ttl(ttl);
     }
 
-    /// ### size
-    /// returns the length of read response
-    /// * Rarely used function
-    /// * path is the 1-wire address of the the device property
-    /// * returns `i32` or error
-    pub fn size(&mut self, path: &str) -> OwEResult<i32> {
-        let msg = self.make_size(path)?;
-        let rcv = self.send_get_single(msg)?;
-        let ret = rcv.ret;
-        if ret < 0 {
-            Err(OwError::Output(format!(
-                "Return code from owserver is error {}",
-                rcv.ret
-            )))
-        } else {
-            Ok(ret)
-        }
+    /// ### invalidate
+    /// forget the cached value of a path
+    /// * the next **read** asks owserver
+    pub fn invalidate(&mut self, path: impl IntoPath) {
+        let path = path.into_path();
+        self.read_cache.remove(|(p, _)| *p == path);
     }
 
-    // Get last base of "filename" excluding blank or blank
-    fn basename(path: &str) -> String {
-        let copy = path.to_string();
-        for n in copy.split('/').rev() {
-            if !n.is_empty() {
-                n.to_string();
-                let m: Vec<&str> = n.split('.').collect();
-                return m[0].to_string();
+    /// ### invalidate_all
+    /// forget every cached value and directory listing
+    pub fn invalidate_all(&mut self) {
+        self.read_cache.clear();
+        self.dir_cache.clear();
+    }
+    /// ### watch
+    /// read a path repeatedly, handing each result to a callback
+    /// * interval -- time between readings (see **Poll**)
+    /// * f -- called with each reading (or error), returns `false` to stop
+    /// * connection is kept open (persistent) between readings
+    ///   * previous persistence restored afterwards
+    /// #### Example
+    /// ```no_run
+    /// use std::time::Duration;
+    /// let mut owserver = owrust::new();
+    /// let mut count = 0;
+    /// owserver.watch("/10.67C6697351FF/temperature", Duration::from_secs(5), |v| {
+    ///     println!("{:?}", v);
+    ///     count += 1;
+    ///     count < 10
+    /// });
+    /// ```
+    pub fn watch<F>(&mut self, path: impl IntoPath, interval: Duration, mut f: F)
+    where
+        F: FnMut(OwEResult<Vec<u8>>) -> bool,
+    {
+        let path = &path.into_path();
+        let persist = self.stream.get_persistence();
+        self.stream.set_persistence(true);
+        self.make_flags();
+
+        let mut poll = Poll::new(interval, false);
+        loop {
+            let stopped = match self.cancel.clone() {
+                Some(stop) => !poll.wait_unless(&stop),
+                None => {
+                    poll.wait();
+                    false
+                }
+            };
+            if stopped {
+                f(self.check_cancel().map(|_| Vec::new()));
+                break;
+            }
+            if !f(self.read(path)) {
+                break;
             }
         }
-        "".to_string()
+
+        self.stream.set_persistence(persist);
+        self.make_flags();
+        if !persist {
+            self.stream.close();
+        }
+    }
+
+    /// ### set_cancel
+    /// shared stop flag for long operations
+    /// * set it (e.g. from a Ctrl-C handler or another thread) to stop
+    ///   * **watch** -- ends promptly, the callback gets a "cancelled" error
+    ///   * **TreeWalker** -- no more entries
+    ///   * multi-packet directory listings -- "cancelled" error
+    /// #### Example
+    /// ```no_run
+    /// use std::sync::{atomic::AtomicBool, Arc};
+    /// let stop = Arc::new(AtomicBool::new(false));
+    /// let mut owserver = owrust::new();
+    /// owserver.set_cancel(stop.clone());
+    /// ```
+    pub fn set_cancel(&mut self, stop: Arc<AtomicBool>) {
+        self.cancel = Some(stop);
+    }
+
+    // has the stop flag been set?
+    fn cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|stop| stop.load(Ordering::Relaxed))
+    }
+
+    fn check_cancel(&self) -> OwEResult<()> {
+        match self.cancelled() {
+            true => Err(OwError::general("cancelled")),
+            _ => Ok(()),
+        }
+    }
+
+    /// ### read_fresh
+    /// reads a value straight from the 1-wire bus
+    /// * UNCACHED flag for this one read (owserver cache bypassed)
+    /// * previous flags restored afterwards, even on error
+    /// * returns a `Vec<u8>` or error
+    pub fn read_fresh(&mut self, path: impl IntoPath) -> OwEResult<Vec<u8>> {
+        let guard = FlagGuard::new(self);
+        guard.owc.flags |= OwMessage::UNCACHED;
+        guard.owc.read(path)
+    }
+
+    /// ### read_checked
+    /// reads a value after checking the path is present
+    /// * a vanished device gives a clear "device not present" error
+    ///   instead of a protocol failure
+    /// * returns a `Vec<u8>` or error
+    pub fn read_checked(&mut self, path: impl IntoPath) -> OwEResult<Vec<u8>> {
+        let path = &path.into_path();
+        if !self.present(path)? {
+            return Err(OwError::General(format!("device not present: {}", path)));
+        }
+        self.read(path)
+    }
+
+    /// ### read_sized
+    /// reads a value from a 1-wire file, checking it against the file size
+    /// * first asks owserver for the size (see **size**)
+    /// * a value shorter than expected may be a partial memory read
+    ///   * expected is the size past _--offset_, at most _--size_ if set
+    ///   * logged as a debug message
+    ///   * an Output error with _--strict_
+    /// * returns a `Vec<u8>` or error
+    pub fn read_sized(&mut self, path: impl IntoPath) -> OwEResult<Vec<u8>> {
+        let path = &path.into_path();
+        let mut size = (self.size(path)? as usize).saturating_sub(self.offset as usize);
+        if self.size != 0 {
+            size = size.min(self.size as usize);
+        }
+        let v = self.read(path)?;
+        if v.len() < size {
+            log::debug!("Short read of {}: {} of {} bytes", path, v.len(), size);
+            if self.strict {
+                return Err(OwError::Output(format!(
+                    "Truncated read of {}: {} of {} bytes",
+                    path,
+                    v.len(),
+                    size
+                )));
+            }
+        }
+        Ok(v)
+    }
+
+    /// ### read_string
+    /// reads a text value from a 1-wire file
+    /// * path is the 1-wire address of the file
+    ///   * (e.g. /system/adapter/name.0)
+    /// * ignores the _--hex_ setting
+    /// * returns `String` or Text error if not valid UTF-8
+    pub fn read_string(&mut self, path: impl IntoPath) -> OwEResult<String> {
+        let path = &path.into_path();
+        let v = self.read(path)?;
+        String::from_utf8(v).map_err(|e| OwError::Text(format!("{} from {}", e, path)))
+    }
+
+    /// ### read_with_time
+    /// reads a value from a 1-wire file and notes when the read completed
+    /// * path is the 1-wire address of the file
+    /// * with _--strict_ the value is checked against the file size (see **read_sized**)
+    /// * returns (`SystemTime`, `Vec<u8>`) or error
+    /// * time can be displayed with **stamp**
+    pub fn read_with_time(&mut self, path: impl IntoPath) -> OwEResult<(SystemTime, Vec<u8>)> {
+        let path = &path.into_path();
+        let v = match self.strict {
+            true => self.read_sized(path)?,
+            _ => self.read(path)?,
+        };
+        Ok((SystemTime::now(), v))
+    }
+    /// ### read_map
+    /// reads a list of 1-wire files, keyed by path
+    /// * paths are 1-wire addresses of files
+    /// * skip_errors chooses how a failing path is handled
+    ///   * owserver's error reply (e.g. no such path) fails like a lost connection
+    ///   * true -- the path is left out of the map
+    ///   * false -- the first error is returned
+    /// * returns a `HashMap` of path to value or error
+    pub fn read_map(
+        &mut self,
+        paths: &[&str],
+        skip_errors: bool,
+    ) -> OwEResult<HashMap<String, Vec<u8>>> {
+        let mut map = HashMap::new();
+        for path in paths {
+            match self.read(*path) {
+                Ok(v) => {
+                    map.insert(path.to_string(), v);
+                }
+                Err(e) => {
+                    if !skip_errors {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+        Ok(map)
+    }
+    /// ### read_many
+    /// reads a list of 1-wire files in order over one connection
+    /// * a quick, ordered snapshot of related values (one thread)
+    /// * connection is kept open (persistent) between the reads
+    ///   * previous persistence restored afterwards
+    /// * returns the values in path order, or the first error
+    pub fn read_many(&mut self, paths: &[&str]) -> OwEResult<Vec<Vec<u8>>> {
+        let persist = self.stream.get_persistence();
+        self.stream.set_persistence(true);
+        self.make_flags();
+
+        let result = paths.iter().map(|path| self.read(*path)).collect();
+
+        self.stream.set_persistence(persist);
+        self.make_flags();
+        if !persist {
+            self.stream.close();
+        }
+        result
+    }
+
+    /// ### read_and_format
+    /// reads a value and appends its unit symbol
+    /// * the unit comes from the property type in the owserver `/structure` tree
+    ///   * temperatures follow the temperature scale (e.g. °C)
+    ///   * pressures follow the pressure scale (e.g. mbar)
+    ///   * voltages (V) and humidity (%RH) are known by name
+    /// * no unit is added if the structure can't be read
+    /// * returns `String` like "21.5 °C" or error
+    pub fn read_and_format(&mut self, path: impl IntoPath) -> OwEResult<String> {
+        let path = &path.into_path();
+        let v = self.read(path)?;
+        let value = self.show_result(v)?.trim().to_string();
+        let structure = match OwMessage::structure_path(path) {
+            Some(s) => match self.read(&s) {
+                Ok(v) => String::from_utf8_lossy(&v).to_string(),
+                Err(_) => String::new(),
+            },
+            None => String::new(),
+        };
+        let unit = self.unit(&structure, &OwMessage::property(path));
+        if unit.is_empty() {
+            Ok(value)
+        } else {
+            Ok(format!("{} {}", value, unit))
+        }
+    }
+
+    // /structure/FF/property entry describing a device property
+    // e.g. /10.67C6697351FF/temperature -> /structure/10/temperature
+    fn structure_path(path: &str) -> Option<String> {
+        let parts: Vec<&str> = path.split('/').filter(|p| !p.is_empty()).collect();
+        let n = parts.len();
+        if n < 2 {
+            return None;
+        }
+        let family = parts[n - 2].split('.').next()?;
+        if family.len() != 2 || !family.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        Some(format!("/structure/{}/{}", family, parts[n - 1]))
+    }
+
+    // last path element (property name) in lower case
+    fn property(path: &str) -> String {
+        path.rsplit('/')
+            .find(|p| !p.is_empty())
+            .unwrap_or("")
+            .to_lowercase()
+    }
+
+    // unit symbol from the structure type letter (first field) and property name
+    fn unit(&self, structure: &str, property: &str) -> &'static str {
+        match structure.split(',').next().unwrap_or("") {
+            "t" | "g" => self.temperature.symbol(),
+            "p" => self.pressure.symbol(),
+            "f" if property.contains("humidity") => "%RH",
+            "f" if property.starts_with("vad")
+                || property.starts_with("vdd")
+                || property.starts_with("volt") =>
+            {
+                "V"
+            }
+            _ => "",
+        }
+    }
+    /// ### write_once
+    /// write a value unless this nonce was already written
+    /// * nonce is chosen by the caller and reused when retrying the same write
+    ///   * e.g. a relayed write retried after a lost response
+    /// * the most recent successful nonces are remembered (per OwMessage)
+    /// * a failed write does not record the nonce, so it can be retried
+    /// * returns `true` if written, `false` if suppressed as a duplicate, or error
+    pub fn write_once(&mut self, nonce: u64, path: impl IntoPath, value: &[u8]) -> OwEResult<bool> {
+        let path = &path.into_path();
+        if self.write_nonces.contains(&nonce) {
+            return Ok(false);
+        }
+        self.write(path, value)?;
+        if self.write_nonces.len() >= OwMessage::WRITE_NONCES {
+            self.write_nonces.pop_front();
+        }
+        self.write_nonces.push_back(nonce);
+        Ok(true)
+    }
+
+    /// ### simultaneous_temperature
+    /// start a temperature conversion on every sensor at once
+    /// * writes `1` to /simultaneous/temperature
+    /// * wait for the conversion (up to 750 ms) then read each `latesttemp`
+    ///   * much faster than reading `temperature` device by device
+    /// * returns () or error
+    pub fn simultaneous_temperature(&mut self) -> OwEResult<()> {
+        self.write("/simultaneous/temperature", b"1")
+    }
+
+    /// ### simultaneous_voltage
+    /// start a voltage conversion on every analog chip at once (e.g. DS2450)
+    /// * writes `1` to /simultaneous/voltage
+    /// * returns () or error
+    pub fn simultaneous_voltage(&mut self) -> OwEResult<()> {
+        self.write("/simultaneous/voltage", b"1")
+    }
+
+    /// ### read_all_temperatures
+    /// reads every temperature sensor on the bus after one shared conversion
+    /// * temperature scale for these readings only (previous scale restored)
+    /// * triggers /simultaneous/temperature and waits for the conversion
+    /// * reads `latesttemp` of each temperature device in the root directory
+    ///   * always from owserver (not the _--cache-ttl_ cache)
+    ///   * a sensor that can't be read is skipped with a log warning
+    /// * returns `Vec` of (device path, value) or error
+    pub fn read_all_temperatures(&mut self, scale: Temperature) -> OwEResult<Vec<(String, f64)>> {
+        let guard = FlagGuard::new(self);
+        guard.owc.temperature = scale;
+        guard.owc.make_flags();
+        guard.owc.all_temperatures()
+    }
+
+    fn all_temperatures(&mut self) -> OwEResult<Vec<(String, f64)>> {
+        self.simultaneous_temperature()?;
+        thread::sleep(self.conversion);
+
+        let mut result: Vec<(String, f64)> = Vec::new();
+        for device in self.dirall("/")? {
+            let family = match OwMessage::leaf_name(&device).parse::<RomId>() {
+                Ok(rom) => rom.family(),
+                Err(_) => continue,
+            };
+            if !OwMessage::TEMPERATURE_FAMILIES.contains(&family) {
+                continue;
+            }
+            let path = format!("{}/latesttemp", device.trim_end_matches('/'));
+            match self.latest_temperature(&path) {
+                Ok(value) => result.push((device, value)),
+                Err(e) => log::warn!("Skipping temperature {}: {}", path, e),
+            }
+        }
+        Ok(result)
+    }
+
+    // one fresh reading as a number
+    fn latest_temperature(&mut self, path: &str) -> OwEResult<f64> {
+        let msg = self.make_read(path)?;
+        let rcv = self.send_get_single(msg)?;
+        if rcv.ret < 0 {
+            return Err(OwError::server(rcv.ret));
+        }
+        let v = OwMessage::value_of(rcv);
+        let text = String::from_utf8_lossy(&v);
+        text.trim()
+            .parse()
+            .map_err(|_| OwError::Numeric(format!("{} from {}", text.trim(), path)))
+    }
+
+    /// ### write
+    /// write a value to a 1-wire file
+    /// * path is the 1-wire address of the file
+    /// * value is a `Vec<u8>` byte sequence to write
+    ///   * (e.g. /10.112233445566/temperature)
+    ///   * may be empty -- a zero-length write (e.g. clearing a register or a trigger like simultaneous)
+    ///   * sent as size 0 with just the path as payload; owserver's return code decides success
+    /// * honors the _--offset_ command line option (start position in memory)
+    /// * returns () or error
+    pub fn write(&mut self, path: impl IntoPath, value: &[u8]) -> OwEResult<()> {
+        let path = &path.into_path();
+        self.read_cache.remove(|(p, _)| p == path);
+        let msg = OwMessage::make_write(self, path, value)?;
+        let rcv = self.send_get_single(msg)?;
+        if rcv.ret == 0 {
+            Ok(())
+        } else {
+            Err(OwError::server(rcv.ret))
+        }
+    }
+
+    /// ### read_range
+    /// reads part of a 1-wire memory file
+    /// * offset -- first byte (ignores _--offset_)
+    /// * size -- bytes requested (ignores _--size_)
+    /// * may return fewer bytes (end of memory gives an empty result)
+    /// * owserver error (negative return code) => `OwError::Server`
+    pub fn read_range(
+        &mut self,
+        path: impl IntoPath,
+        offset: u32,
+        size: u32,
+    ) -> OwEResult<Vec<u8>> {
+        let path = &path.into_path();
+        let mut msg = self.query(OwQuery::READ, path, None)?;
+        msg.size = size;
+        msg.offset = offset;
+        let rcv = self.send_get_single(msg)?;
+        if rcv.ret < 0 {
+            return Err(OwError::server(rcv.ret));
+        }
+        Ok(OwMessage::value_of(rcv))
+    }
+
+    /// ### write_at
+    /// write to part of a 1-wire memory file
+    /// * offset -- first byte (ignores _--offset_)
+    pub fn write_at(&mut self, path: impl IntoPath, offset: u32, value: &[u8]) -> OwEResult<()> {
+        let path = &path.into_path();
+        self.read_cache.remove(|(p, _)| p == path);
+        let mut msg = self.query(OwQuery::WRITE, path, Some(value))?;
+        msg.offset = offset;
+        let rcv = self.send_get_single(msg)?;
+        if rcv.ret == 0 {
+            Ok(())
+        } else {
+            Err(OwError::server(rcv.ret))
+        }
+    }
+
+    /// ### write_preview
+    /// the WRITE message **write** would send, without sending it
+    /// * for the _--dry-run_ option -- nothing is connected or written
+    /// * path, value (hex), size and offset as in **owsnoop**
+    pub fn write_preview(&self, path: impl IntoPath, value: &[u8]) -> OwEResult<Vec<String>> {
+        let path = &path.into_path();
+        let msg = self.make_write(path, value)?;
+        Ok(msg.print_all("Dry run (not sent)").to_vec())
+    }
+
+    /// ### dry_run
+    /// is the _--dry-run_ option set?
+    /// * writes should be shown (**write_preview**) rather than sent
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// ### write_str
+    /// write text to a 1-wire file
+    /// * the UTF-8 bytes of value (e.g. "1" to a PIO)
+    /// * otherwise like **write**
+    pub fn write_str(&mut self, path: impl IntoPath, value: &str) -> OwEResult<()> {
+        self.write(path, value.as_bytes())
+    }
+
+    /// ### write_hex
+    /// write bytes given as a hex string to a 1-wire file
+    /// * e.g. "48656C6C6F" writes "Hello"
+    /// * hex whatever the _--hex_ setting
+    /// * Numeric error for odd length or bad characters
+    /// * otherwise like **write**
+    pub fn write_hex(&mut self, path: impl IntoPath, hex: &str) -> OwEResult<()> {
+        let value = OwMessage::hex_bytes(hex)?;
+        self.write(path, &value)
+    }
+
+    /// ### open_reader
+    /// `std::io::Read` over a 1-wire memory file, starting at the beginning
+    /// * e.g. `std::io::copy` device memory into a file
+    pub fn open_reader(&mut self, path: impl IntoPath) -> DeviceReader<'_> {
+        let path = path.into_path();
+        DeviceReader::new(self, path)
+    }
+
+    /// ### open_writer
+    /// `std::io::Write` over a 1-wire memory file, starting at the beginning
+    pub fn open_writer(&mut self, path: impl IntoPath) -> DeviceWriter<'_> {
+        let path = path.into_path();
+        DeviceWriter::new(self, path)
+    }
+
+    /// ### dirall
+    /// returns the path directory listing
+    /// * uses a separate message for each entry
+    /// * honors the _--dir_ command line option
+    /// * honors the _--bare_ command line option
+    /// * honors the _--first-only_ command line option
+    /// * returns `Vec<u8>` or error
+    /// * result can be displayed with **show_text**
+    pub fn dir(&mut self, path: impl IntoPath) -> OwEResult<Vec<String>> {
+        let path = &path.into_path();
+        let msg = self.make_dir(path)?;
+        if self.first_only {
+            return self.dir_first(msg);
+        }
+        let mut rcv = self.send_get_many(msg)?;
+        self.dirboth(&mut rcv.content)
+    }
+
+    // first entry of a DIR listing (_--first-only_)
+    // * stops reading once an entry gets past the filters (_--bare_ etc)
+    // * the rest of the listing is abandoned along with the connection
+    fn dir_first(&mut self, mut msg: OwQuery) -> OwEResult<Vec<String>> {
+        self.send_packet(&mut msg)?;
+        for _ in 0..self.max_packets {
+            let mut rcv = self.get_msg_single()?;
+            if rcv.payload == 0 {
+                // whole listing read, nothing passed
+                self.stream.finish();
+                self.show_profile();
+                return Ok(Vec::new());
+            }
+            let mut entries = self.dirboth(&mut rcv.content)?;
+            if !entries.is_empty() {
+                entries.truncate(1);
+                if let Some(stream) = self.stream.take() {
+                    let _ = stream.shutdown(std::net::Shutdown::Both);
+                }
+                self.show_profile();
+                return Ok(entries);
+            }
+        }
+        Err(OwError::General(format!(
+            "Directory exceeded {} continuation packets",
+            self.max_packets
+        )))
+    }
+
+    /// ### present
+    /// returns the existence of a 1-wire device
+    /// * Rarely used function
+    /// * path is the 1-wire address of the the device
+    /// * returns bool or error
+    /// ### owserver return codes
+    /// * 0 (or positive) -- present => `Ok(true)`
+    /// * -2 (ENOENT) -- not present => `Ok(false)`
+    /// * other negative -- owserver error => `OwError::Server`
+    pub fn present(&mut self, path: impl IntoPath) -> OwEResult<bool> {
+        let path = &path.into_path();
+        let msg = self.make_present(path)?;
+        let rcv = self.send_get_single(msg)?;
+        match rcv.ret {
+            r if r >= 0 => Ok(true),
+            OwMessage::NOT_PRESENT => Ok(false),
+            r => Err(OwError::server(r)),
+        }
+    }
+
+    /// ### size
+    /// returns the length of read response
+    /// * Rarely used function
+    /// * path is the 1-wire address of the the device property
+    /// * returns `i32` or error
+    pub fn size(&mut self, path: impl IntoPath) -> OwEResult<i32> {
+        let path = &path.into_path();
+        let msg = self.make_size(path)?;
+        let rcv = self.send_get_single(msg)?;
+        let ret = rcv.ret;
+        if ret < 0 {
+            Err(OwError::server(rcv.ret))
+        } else {
+            Ok(ret)
+        }
+    }
+
+    /// ### is_address
+    /// is a path element a device address?
+    /// * e.g. 10.67C6697351FF, 10.67C6697351FF.8D or 1067C6697351FF8D
+    pub fn is_address(name: &str) -> bool {
+        let hex: Vec<char> = name.chars().filter(|&c| c != '.').collect();
+        (hex.len() == 14 || hex.len() == 16) && hex.iter().all(|c| c.is_ascii_hexdigit())
+    }
+
+    // change the case of the device addresses in a path
+    // * other path elements (properties, directories) are untouched
+    fn address_case(path: &str, case: &AddressCase) -> String {
+        let recase = |segment: &str| match case {
+            AddressCase::UPPER if OwMessage::is_address(segment) => segment.to_ascii_uppercase(),
+            AddressCase::LOWER if OwMessage::is_address(segment) => segment.to_ascii_lowercase(),
+            _ => segment.to_string(),
+        };
+        path.split('/')
+            .map(recase)
+            .collect::<Vec<String>>()
+            .join("/")
+    }
+
+    // Get last base of "filename" excluding blank or blank
+    // * device address (e.g. 10.67C6697351FF or 10.67C6697351FF.8D) => family "10"
+    // * numeric index removed (e.g. bus.0 => bus)
+    // * other dotted names kept whole (e.g. pages.ALL)
+    fn basename(path: &str) -> String {
+        let name = path.rsplit('/').find(|n| !n.is_empty()).unwrap_or("");
+        let (stem, rest) = match name.split_once('.') {
+            Some(parts) => parts,
+            None => return name.to_string(),
+        };
+        if OwMessage::is_address(name) {
+            return name.chars().filter(|&c| c != '.').take(2).collect();
+        }
+        if rest
+            .split('.')
+            .all(|i| !i.is_empty() && i.chars().all(|c| c.is_ascii_digit()))
+        {
+            return stem.to_string();
+        }
+        name.to_string()
     }
-    // dirboth prunes nulls and possibly the prunelist if --prune specified
+    // dirboth prunes nulls, owserver's own top-level entries if --bare and the prunelist if --prune specified
+    // then applies --include and --exclude patterns
     pub fn dirboth(&self, raw_dir: &mut Vec<u8>) -> OwEResult<Vec<String>> {
         raw_dir.retain(|&b| b != 0);
         let mut s: Vec<&str> = str::from_utf8(raw_dir)?.split(',').collect();
+        if self.bare {
+            // top level: devices (by address or alias) but not bus.N, settings, ...
+            // * deeper listings are device contents
+            s.retain(|&x| {
+                x.trim_matches('/').contains('/')
+                    || tree::EntryKind::of(x, x.ends_with('/')) != tree::EntryKind::Virtual
+            });
+        }
         if self.prune {
-            let prune_list: Vec<&str> = vec![
-                "address",
-                "crc8",
-                "family",
-                "id",
-                "locator",
-                "r_address",
-                "r_id",
-                "r_locator",
-                "type",
-                "bus",
-            ];
-            s.retain(|&x| !prune_list.contains(&OwMessage::basename(x).as_str()));
+            s.retain(|&x| !tree::PRUNE_LIST.contains(&OwMessage::basename(x).as_str()));
+        }
+        if !self.include.is_empty() {
+            s.retain(|&x| {
+                let base = OwMessage::basename(x);
+                self.include.iter().any(|p| glob_match(p, &base))
+            });
+        }
+        if !self.exclude.is_empty() {
+            s.retain(|&x| {
+                let base = OwMessage::basename(x);
+                !self.exclude.iter().any(|p| glob_match(p, &base))
+            });
         }
         Ok(s.into_iter().map(String::from).collect())
     }
@@ -541,14 +1831,77 @@ impl OwMessage {
     /// * honors the _--dir_ command line option
     /// * honors the _--bare_ command line option
     /// * removes some stray null bytes erroneously added by original owserver to file names
+    /// * honors the _--first-only_ command line option
     /// * returns `Vec<String>` or error
-    pub fn dirall(&mut self, path: &str) -> OwEResult<Vec<String>> {
+    pub fn dirall(&mut self, path: impl IntoPath) -> OwEResult<Vec<String>> {
+        let path = &path.into_path();
         let mut d: Vec<u8> = match self.slash {
-            true => self.get_value(path, OwMessage::make_dirallslash),
-            _ => self.get_value(path, OwMessage::make_dirall),
-        }?;
-        self.dirboth(&mut d)
+            true => self.dir_raw(path, OwMessage::make_dirallslash)?,
+            _ => self.dir_raw(path, OwMessage::make_dirall)?,
+        };
+        let mut entries = self.dirboth(&mut d)?;
+        if self.first_only {
+            entries.truncate(1);
+        }
+        Ok(entries)
+    }
+
+    // owserver's listing, as received (cached if _--dir-cache-ttl_)
+    // * cached by path, message type and flags (e.g. BUS_RET)
+    // * filters (_--bare_, _--prune_, ...) are applied afterwards, on every call
+    // * owserver errors are not cached
+    fn dir_raw(
+        &mut self,
+        path: &str,
+        f: fn(&OwMessage, &str) -> OwEResult<OwQuery>,
+    ) -> OwEResult<Vec<u8>> {
+        let msg = f(self, path)?;
+        let key = (path.to_string(), [msg.mtype, self.flags]);
+        let cache = self.use_cache();
+        if cache {
+            if let Some(raw) = self.dir_cache.get(&key) {
+                return Ok(raw);
+            }
+        }
+        let rcv = self.send_get_single(msg)?;
+        let raw = OwMessage::checked_value(rcv)?;
+        if cache {
+            self.dir_cache.put(key, raw.clone());
+        }
+        Ok(raw)
+    }
+
+    /// ### set_dir_cache_ttl
+    /// Keep directory listings for reuse (the _--dir-cache-ttl_ option)
+    /// * **dirall** and **dirallslash** answer from the cache while a listing is younger than ttl
+    /// * owserver's reply is kept, so _--bare_, _--prune_ etc. still apply
+    /// * None (default) for no caching
+    /// * ignored with _--via-uncached_ and the UNCACHED flag
+    pub fn set_dir_cache_ttl(&mut self, ttl: Option<Duration>) {
+        self.dir_cache.set_ttl(ttl);
+    }
+    /// ### dirall_names
+    /// returns the leaf names of the path directory listing
+    /// * e.g. `temperature` rather than `/10.67C6697351FF/temperature`
+    /// * uses **dirall** (see **leaf_name**)
+    /// * returns `Vec<String>` or error
+    pub fn dirall_names(&mut self, path: impl IntoPath) -> OwEResult<Vec<String>> {
+        let entries = self.dirall(path)?;
+        Ok(entries
+            .iter()
+            .map(|e| OwMessage::leaf_name(e).to_string())
+            .collect())
+    }
+
+    /// ### leaf_name
+    /// last element of a directory entry
+    /// * `/10.67C6697351FF/temperature` => `temperature`
+    /// * directories keep their name: `/10.67C6697351FF/errata/` => `errata`
+    /// * `/` => empty
+    pub fn leaf_name(entry: &str) -> &str {
+        entry.trim_end_matches('/').rsplit('/').next().unwrap_or("")
     }
+
     /// ### dirallslash
     /// returns the path directory listing
     /// * efficiently uses a single message
@@ -556,11 +1909,177 @@ impl OwMessage {
     /// * honors the _--bare_ command line option
     /// * removes some stray null bytes erroneously added by original owserver to file names
     /// * returns `Vec<String>` or error
-    pub fn dirallslash(&mut self, path: &str) -> OwEResult<Vec<String>> {
-        let mut d: Vec<u8> = self.get_value(path, OwMessage::make_dirallslash)?;
+    pub fn dirallslash(&mut self, path: impl IntoPath) -> OwEResult<Vec<String>> {
+        let path = &path.into_path();
+        let mut d: Vec<u8> = self.dir_raw(path, OwMessage::make_dirallslash)?;
         self.dirboth(&mut d)
     }
 
+    /// ### dir_filter
+    /// returns the path directory listing, keeping only entries the predicate accepts
+    /// * uses **dirall** for the listing (so _--bare_, _--include_ and _--exclude_ apply first)
+    /// * predicate sees each full entry (e.g. `/10.67C6697351FF`)
+    /// * returns `Vec<String>` or error
+    /// #### Example
+    /// ```no_run
+    /// let mut owserver = owrust::new();
+    /// let sensors = owserver.dir_filter("/", |e| e.starts_with("/28.")).unwrap();
+    /// ```
+    pub fn dir_filter<F>(&mut self, path: impl IntoPath, pred: F) -> OwEResult<Vec<String>>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let path = &path.into_path();
+        let mut entries = self.dirall(path)?;
+        entries.retain(|e| pred(e));
+        Ok(entries)
+    }
+
+    /// ### dir_present
+    /// returns the path directory listing with presence of each entry
+    /// * uses **dirall** for the listing
+    /// * checks each entry with **present**
+    ///   * a cached listing may still name devices no longer on the bus
+    /// * returns `Vec<(String, bool)>` or error
+    pub fn dir_present(&mut self, path: impl IntoPath) -> OwEResult<Vec<(String, bool)>> {
+        let path = &path.into_path();
+        let entries = self.dirall(path)?;
+        let mut result: Vec<(String, bool)> = Vec::new();
+        for entry in entries {
+            let present = self.present(&entry)?;
+            result.push((entry, present));
+        }
+        Ok(result)
+    }
+
+    /// ### bus_info
+    /// adapter details of a 1-wire bus (for diagnosing hardware problems)
+    /// * walks every file under /bus.N/interface (settings and statistics)
+    /// * names are relative to the interface directory (e.g. `settings/name`)
+    /// * values are shown as text, trimmed
+    /// * returns `Vec` of (name, value) or error
+    pub fn bus_info(&mut self, bus: u32) -> OwEResult<Vec<(String, String)>> {
+        let root = format!("/bus.{}/interface/", bus);
+        let mut result: Vec<(String, String)> = Vec::new();
+        let mut pending: Vec<String> = vec![root.clone()];
+        while let Some(dir) = pending.pop() {
+            for entry in self.dirallslash(&dir)? {
+                if entry.is_empty() {
+                    continue;
+                }
+                if entry.ends_with('/') {
+                    pending.push(entry);
+                    continue;
+                }
+                let value = self.read(&entry)?;
+                let name = entry.strip_prefix(&root).unwrap_or(&entry).to_string();
+                result.push((name, String::from_utf8_lossy(&value).trim().to_string()));
+            }
+        }
+        result.sort();
+        Ok(result)
+    }
+
+    /// ### probe
+    /// what 1-wire hardware is attached
+    /// * lists the `bus.N` entries of the root directory
+    ///   * shown even with _--bare_
+    /// * adapter name of each bus from `/system/adapter/name.N`
+    /// * returns `Vec` of (bus path, adapter name) or error
+    pub fn probe(&mut self) -> OwEResult<Vec<(String, String)>> {
+        let root = {
+            let guard = FlagGuard::new(self);
+            guard.owc.flags |= OwMessage::BUS_RET;
+            guard.owc.bare = false;
+            guard.owc.dirall("/")
+        };
+
+        let mut result: Vec<(String, String)> = Vec::new();
+        for (bus, n) in OwMessage::bus_paths(&root?) {
+            let v = self.read(format!("/system/adapter/name.{}", n))?;
+            result.push((bus, String::from_utf8_lossy(&v).trim().to_string()));
+        }
+        Ok(result)
+    }
+
+    /// ### scan
+    /// every 1-wire device in the root directory
+    /// * non-device entries (bus.N, settings, ...) are skipped
+    /// * addresses with a bad check byte (crc8) are skipped with a log warning
+    /// * returns `Vec<RomId>` in directory order, or error
+    pub fn scan(&mut self) -> OwEResult<Vec<RomId>> {
+        let root = {
+            let guard = FlagGuard::new(self);
+            (guard.owc.bare, guard.owc.first_only) = (true, false);
+            guard.owc.dirall("/")
+        };
+
+        let mut devices = Vec::new();
+        for entry in root? {
+            let name = OwMessage::leaf_name(&entry);
+            if !OwMessage::is_address(name) {
+                continue;
+            }
+            match name.parse::<RomId>() {
+                Ok(rom) => devices.push(rom),
+                Err(e) => log::warn!("Skipping device {}: {}", entry, e),
+            }
+        }
+        Ok(devices)
+    }
+
+    /// ### aliases
+    /// friendly names of the devices (from each device's `alias` property)
+    /// * devices from **scan**
+    /// * devices without an alias (empty or unreadable) are left out
+    /// * returns `Vec` of (device, alias), or error
+    pub fn aliases(&mut self) -> OwEResult<Vec<(RomId, String)>> {
+        let mut result = Vec::new();
+        for rom in self.scan()? {
+            let path = format!("/{}/alias", rom.to_format(&Format::FdI));
+            match self.read(&path) {
+                Ok(v) => {
+                    let alias = String::from_utf8_lossy(&v).trim().to_string();
+                    if !alias.is_empty() {
+                        result.push((rom, alias));
+                    }
+                }
+                Err(e) => log::debug!("No alias for {}: {}", path, e),
+            }
+        }
+        Ok(result)
+    }
+
+    /// ### alias_lookup
+    /// reverse of **aliases** -- device for each alias
+    /// #### Example
+    /// ```no_run
+    /// use owrust::OwMessage;
+    /// let mut owserver = owrust::new();
+    /// let aliases = owserver.aliases().unwrap();
+    /// let devices = OwMessage::alias_lookup(&aliases);
+    /// let boiler = devices.get("boiler");
+    /// ```
+    pub fn alias_lookup(aliases: &[(RomId, String)]) -> HashMap<String, RomId> {
+        aliases
+            .iter()
+            .map(|(rom, alias)| (alias.clone(), *rom))
+            .collect()
+    }
+
+    // bus entries (e.g. /bus.0) of a directory with their number, in bus order
+    fn bus_paths(entries: &[String]) -> Vec<(String, u32)> {
+        let mut buses: Vec<(String, u32)> = entries
+            .iter()
+            .filter_map(|e| {
+                let n = OwMessage::leaf_name(e).strip_prefix("bus.")?.parse().ok()?;
+                Some((e.clone(), n))
+            })
+            .collect();
+        buses.sort_by_key(|b| b.1);
+        buses
+    }
+
     /// ### get
     /// combines **dir** and **read** functionality
     /// * _read_ if path is a file
@@ -568,47 +2087,278 @@ impl OwMessage {
     /// * honors the _--dir_ command line option
     /// * honors the _--hex_ command line option
     /// * honors the _--bare_ command line option
-    /// * returns `Vec<u8>` or error
-    /// * result can be displayed with **show_result**
-    pub fn get(&mut self, path: &str) -> OwEResult<Vec<u8>> {
-        match self.slash {
+    /// * returns GetResult or error
+    ///   * Value can be displayed with **show_result**
+    ///   * Dir entries are filtered like **dirall** (_--include_, _--prune_, ...)
+    pub fn get(&mut self, path: impl IntoPath) -> OwEResult<GetResult> {
+        let path = &path.into_path();
+        let mut v = match self.slash {
             true => self.get_value(path, OwMessage::make_getslash),
             _ => self.get_value(path, OwMessage::make_get),
+        }?;
+        match OwMessage::is_listing(path, &v) {
+            true => Ok(GetResult::Dir(self.dirboth(&mut v)?)),
+            _ => Ok(GetResult::Value(v)),
         }
     }
 
+    // owserver answers GET on a directory with a listing
+    // * comma separated full paths of the children (e.g. /10.67C6697351FF/temperature)
+    // * a value can look like a path (e.g. /dev/ttyUSB0), but not like a child of path
+    fn is_listing(path: &str, v: &[u8]) -> bool {
+        let Ok(text) = str::from_utf8(v) else {
+            return false;
+        };
+        let text = text.trim_end_matches('\0');
+        let base = path.trim_matches('/');
+        let (child, uncached_child) = match base.is_empty() {
+            true => ("/".to_string(), "/uncached/".to_string()),
+            _ => (format!("/{}/", base), format!("/uncached/{}/", base)),
+        };
+        !text.is_empty()
+            && text.split(',').all(|e| {
+                let e = e.trim_matches('\0');
+                (e.starts_with(&child) || e.starts_with(&uncached_child))
+                    && !e.chars().any(|c| c.is_whitespace() || c.is_control())
+            })
+    }
+
     /// ### show_result
     /// prints the result of an owserver query
     /// * honors the hex setting
+    /// * values that aren't valid UTF-8 are shown as hex with a note
+    ///   * unless _--strict_ is set, which returns an error instead
+    /// * honors the _--trim_ setting (text without owserver's space padding)
     /// * good for **read** and **get**
     pub fn show_result(&self, v: Vec<u8>) -> OwEResult<String> {
         if self.hex {
-            Ok(v.iter()
-                .map(|b| format!("{:02X}", b))
-                .collect::<Vec<String>>()
-                .join(" "))
+            Ok(OwMessage::hex_string(&v))
         } else {
-            let s = str::from_utf8(&v)?;
-            Ok(s.to_string())
+            match str::from_utf8(&v) {
+                Ok(s) if self.trim => Ok(s.trim_ascii().to_string()),
+                Ok(s) => Ok(s.to_string()),
+                Err(e) if self.strict => Err(e.into()),
+                Err(_) => Ok(format!("(not UTF-8) {}", OwMessage::hex_string(&v))),
+            }
         }
     }
 
-    /// ### input_to_write
-    /// take the value string for **owwrite**
-    /// * if not --hex, use str as bytes directly, else
-    /// * read as a hex string
-    pub fn input_to_write(&self, s: &str) -> OwEResult<Vec<u8>> {
-        if !self.hex {
-            return Ok(s.as_bytes().to_vec());
+    /// ### unit_symbol
+    /// unit of the value at a path, judged by the property name
+    /// * temperatures (e.g. temperature, temperature12, latesttemp, templow) -- the _--temperature_ scale
+    /// * pressures (e.g. pressure) -- the _--pressure_ scale
+    /// * only for device properties (a device address in the path)
+    ///   * e.g. not `/structure/10/temperature`
+    /// * None for anything else
+    pub fn unit_symbol(&self, path: &str) -> Option<&'static str> {
+        let name = OwMessage::leaf_name(path).to_ascii_lowercase();
+        let mut above = path.trim_end_matches('/').rsplit('/').skip(1);
+        if !above.any(OwMessage::is_address) {
+            None
+        } else if name.contains("temp") {
+            Some(self.temperature.symbol())
+        } else if name.contains("pressure") {
+            Some(self.pressure.symbol())
+        } else {
+            None
         }
-        // hex
-        if !s.len().is_multiple_of(2) {
-            return Err(OwError::Numeric(
-                "Hex string should be an even length".into(),
-            ));
+    }
+
+    /// ### show_units
+    /// reading text with its unit appended (e.g. `85.75 °F`)
+    /// * only with the _--units_ option, and not for _--hex_
+    /// * unchanged if the path has no known unit or the text is empty
+    pub fn show_units(&self, path: &str, text: String) -> String {
+        if !self.units || self.hex || text.trim().is_empty() {
+            return text;
         }
-        (0..s.len())
-            .step_by(2)
+        match self.unit_symbol(path) {
+            Some(symbol) => format!("{} {}", text.trim_end(), symbol),
+            None => text,
+        }
+    }
+
+    /// ### show_size
+    /// text for a **size** result
+    /// * plain byte count, or with units if _--human_ is set
+    pub fn show_size(&self, size: i32) -> String {
+        if self.human {
+            OwMessage::format_size(size)
+        } else {
+            size.to_string()
+        }
+    }
+
+    /// ### format_size
+    /// byte count with binary units
+    /// * `12 B`, `2.0 KiB`, `1.5 MiB`
+    /// * whole bytes below 1024
+    pub fn format_size(bytes: i32) -> String {
+        const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+        if bytes.abs() < 1024 {
+            return format!("{} B", bytes);
+        }
+        let mut value = bytes as f64 / 1024.0;
+        let mut unit = 0;
+        while value.abs() >= 1024.0 && unit + 1 < UNITS.len() {
+            value /= 1024.0;
+            unit += 1;
+        }
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+
+    // space separated hex bytes
+    fn hex_string(v: &[u8]) -> String {
+        v.iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    /// ### discover
+    /// Find owservers advertised with zeroconf (mDNS)
+    /// * browses `_owserver._tcp` for a few seconds
+    /// * returns candidate `host:port` targets (for the _-s_ option) or error
+    /// * needs the `mdns` feature
+    #[cfg(feature = "mdns")]
+    pub fn discover() -> OwEResult<Vec<String>> {
+        discover::browse(discover::SERVICE, std::time::Duration::from_secs(3))
+    }
+
+    /// ### columns
+    /// number of display columns from the _--columns_ option
+    /// * 0 (the default) means one entry per line
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// ### format
+    /// device address format from the _--format_ option
+    pub fn format(&self) -> &Format {
+        &self.format
+    }
+
+    /// ### quiet
+    /// is the _--quiet_ option set?
+    /// * problems with individual paths are not shown
+    pub fn quiet(&self) -> bool {
+        self.quiet
+    }
+
+    /// ### trouble
+    /// show a problem with one path on stderr
+    /// * unless _--quiet_
+    /// * the program should still report failure (exit code)
+    pub fn trouble(&self, message: impl std::fmt::Display) {
+        if let Some(m) = self.trouble_text(message) {
+            eprintln!("{}", m);
+        }
+    }
+
+    // message text, or None if _--quiet_
+    fn trouble_text(&self, message: impl std::fmt::Display) -> Option<String> {
+        match self.quiet {
+            true => None,
+            false => Some(message.to_string()),
+        }
+    }
+
+    /// ### color
+    /// should output be colored? (the _--color_ option)
+    /// * auto (default) -- only if the screen is a terminal (never with _--output_ FILE)
+    pub fn color(&self) -> bool {
+        match self.color {
+            Color::ALWAYS => true,
+            Color::NEVER => false,
+            Color::AUTO => crate::console::console_is_terminal(),
+        }
+    }
+
+    /// ### tree_style
+    /// lines between **owtree** entries (the _--style_ option)
+    pub fn tree_style(&self) -> &TreeStyle {
+        &self.tree_style
+    }
+
+    /// ### poll
+    /// pacing for repeated readings from the _--interval_ option
+    /// * None for a single reading
+    /// * honors _--interval-align_
+    pub fn poll(&self) -> Option<Poll> {
+        self.interval
+            .map(|interval| Poll::new(interval, self.align))
+    }
+
+    /// ### timestamp
+    /// is the _--timestamp_ option set?
+    pub fn timestamp(&self) -> bool {
+        self.timestamp
+    }
+
+    /// ### stamp
+    /// prefix for an output line from the _--timestamp_ option
+    /// * RFC3339 UTC time (e.g. `2025-06-01T12:30:05Z `)
+    /// * seconds since 1970 with _--epoch_ (e.g. `1748781005.250 `)
+    /// * empty without _--timestamp_
+    pub fn stamp(&self, time: SystemTime) -> String {
+        if !self.timestamp {
+            return String::new();
+        }
+        let since = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        if self.epoch {
+            format!("{}.{:03} ", since.as_secs(), since.subsec_millis())
+        } else {
+            format!("{} ", rfc3339(since.as_secs()))
+        }
+    }
+
+    /// ### csv
+    /// is the _--csv_ option set?
+    /// * readings should be written as `path,value` rows
+    pub fn csv(&self) -> bool {
+        self.csv
+    }
+
+    /// ### raw
+    /// is the _--raw_ option set?
+    /// * values should be written out as unchanged bytes
+    /// * no formatting, no trailing newline
+    pub fn raw(&self) -> bool {
+        self.raw
+    }
+
+    /// ### separator
+    /// byte that ends each output entry
+    /// * NUL with the _--null_ option (for `xargs -0`)
+    /// * newline otherwise
+    pub fn separator(&self) -> u8 {
+        match self.null {
+            true => b'\0',
+            _ => b'\n',
+        }
+    }
+
+    /// ### input_to_write
+    /// take the value string for **owwrite**
+    /// * if not --hex, use str as bytes directly, else
+    /// * read as a hex string
+    pub fn input_to_write(&self, s: &str) -> OwEResult<Vec<u8>> {
+        if !self.hex {
+            return Ok(s.as_bytes().to_vec());
+        }
+        OwMessage::hex_bytes(s)
+    }
+
+    // bytes from a hex string (e.g. "0A1B")
+    fn hex_bytes(s: &str) -> OwEResult<Vec<u8>> {
+        if !s.len().is_multiple_of(2) {
+            return Err(OwError::numeric("Hex string should be an even length"));
+        }
+        if !s.is_ascii() {
+            return Err(OwError::Numeric(format!("Bad hex characters in {}", s)));
+        }
+        (0..s.len())
+            .step_by(2)
             .map(|i| match u8::from_str_radix(&s[i..i + 2], 16) {
                 Ok(byte) => Ok(byte),
                 Err(e) => Err(OwError::Numeric(format!("Bad hex characters {}", e))),
@@ -618,36 +2368,293 @@ impl OwMessage {
 
     /// ### listen
     /// start an owserver (that forwards packets with some processing)
-    /// * Uses threads
+    /// * Uses a fixed pool of worker threads (_--workers_)
+    ///   * connections beyond that wait their turn
+    /// * each connection relays with its own token
+    ///   * a query carrying any of them has looped back (and is dropped)
+    /// * periodic status on stderr with _--heartbeat_
     pub fn listen(&self) -> OwEResult<()> {
         if let Some(address) = &self.listener {
-            let listen_stream = TcpListener::bind(address)?;
-            for stream in listen_stream.incoming() {
-                match stream {
-                    Ok(stream) => {
-                        // cloned owserver will have closed (outgoing) stream
-                        let mut instance = OwServerInstance::new(self.clone(), stream);
-                        thread::spawn(move || {
-                            instance.handle_query();
-                        });
-                    }
-                    Err(e) => {
-                        eprintln!("{}", e);
+            let listen_stream = OwMessage::bind(address)?;
+            let handled = Arc::new(AtomicUsize::new(0));
+            if let Some(every) = self.heartbeat {
+                let address = address.clone();
+                let handled = handled.clone();
+                thread::spawn(move || loop {
+                    thread::sleep(every);
+                    eprintln!(
+                        "Listening on {}, {} connections handled",
+                        address,
+                        handled.load(Ordering::Relaxed)
+                    );
+                });
+            }
+            self.serve(listen_stream, handled);
+        } else {
+            return Err(OwError::general("No address given to listen on (--port)"));
+        }
+        Ok(())
+    }
+
+    // listening socket, with a clear message for the common failures
+    fn bind(address: &str) -> OwEResult<TcpListener> {
+        TcpListener::bind(address).map_err(|e| {
+            let hint = match e.kind() {
+                std::io::ErrorKind::AddrInUse => {
+                    " (port already in use -- is another owsnoop or owserver running?)"
+                }
+                std::io::ErrorKind::AddrNotAvailable => " (not an address of this computer)",
+                std::io::ErrorKind::PermissionDenied => " (ports below 1024 need privileges)",
+                _ => "",
+            };
+            OwError::General(format!("Cannot listen on {}: {}{}", address, e, hint))
+        })
+    }
+
+    // Default worker count when the CPU count is unknown
+    const DEFAULT_WORKERS: usize = 8;
+
+    // number of connections handled at once
+    fn worker_count(&self) -> usize {
+        match self.workers {
+            0 => thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(OwMessage::DEFAULT_WORKERS),
+            n => n,
+        }
+    }
+
+    // accept connections and hand them to the worker threads
+    // * handled counts the finished connections
+    fn serve(&self, listen_stream: TcpListener, handled: Arc<AtomicUsize>) {
+        let relay_tokens: RelayTokens = Arc::new(Mutex::new(Vec::new()));
+        let (tx, rx) = mpsc::channel::<TcpStream>();
+        let rx = Arc::new(Mutex::new(rx));
+        for _ in 0..self.worker_count() {
+            // cloned owserver will have closed (outgoing) stream
+            let message = self.clone();
+            let relay_tokens = relay_tokens.clone();
+            let rx = rx.clone();
+            let handled = handled.clone();
+            thread::spawn(move || loop {
+                let stream = match rx.lock() {
+                    Ok(rx) => rx.recv(),
+                    Err(_) => return,
+                };
+                let Ok(stream) = stream else {
+                    return;
+                };
+                // instance has its own token
+                let mut instance =
+                    OwServerInstance::new(message.clone(), stream, relay_tokens.clone());
+                instance.handle_query();
+                handled.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        for stream in listen_stream.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if tx.send(stream).is_err() {
+                        eprintln!("No workers left to handle connections");
+                        return;
                     }
                 }
+                Err(e) => {
+                    eprintln!("{}", e);
+                }
+            }
+        }
+    }
+}
+
+// Restores the owserver flags (and the scales they are made from) when dropped
+// * also the listing options (_--bare_, _--first-only_)
+// * for temporary changes that must not outlive one call
+struct FlagGuard<'a> {
+    owc: &'a mut OwMessage,
+    flags: u32,
+    temperature: Temperature,
+    pressure: Pressure,
+    bare: bool,
+    first_only: bool,
+}
+impl<'a> FlagGuard<'a> {
+    fn new(owc: &'a mut OwMessage) -> Self {
+        let flags = owc.flags;
+        let temperature = owc.temperature.clone();
+        let pressure = owc.pressure.clone();
+        let (bare, first_only) = (owc.bare, owc.first_only);
+        FlagGuard {
+            owc,
+            flags,
+            temperature,
+            pressure,
+            bare,
+            first_only,
+        }
+    }
+}
+impl Drop for FlagGuard<'_> {
+    fn drop(&mut self) {
+        self.owc.flags = self.flags;
+        self.owc.temperature = self.temperature.clone();
+        self.owc.pressure = self.pressure.clone();
+        self.owc.bare = self.bare;
+        self.owc.first_only = self.first_only;
+    }
+}
+
+// read_exact for owserver packets
+// * a connection closed before any byte is an UnexpectedEof IO error (peer hung up)
+// * a connection closed part way is an Output error saying how much arrived
+// * other IO errors (e.g. timeout) are passed on
+fn read_exactly<R: Read>(stream: &mut R, buf: &mut [u8], part: &str) -> OwEResult<()> {
+    let mut got = 0;
+    while got < buf.len() {
+        match stream.read(&mut buf[got..]) {
+            Ok(0) if got == 0 => {
+                return Err(OwError::Io(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    format!("connection closed before {}", part),
+                )))
             }
+            Ok(0) => {
+                return Err(OwError::Output(format!(
+                    "truncated owserver response: {} expected {} bytes, received {}",
+                    part,
+                    buf.len(),
+                    got
+                )))
+            }
+            Ok(n) => got += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => (),
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(())
+}
+
+// RFC3339 UTC time from seconds since 1970
+// (days to civil date from Howard Hinnant's algorithm)
+fn rfc3339(secs: u64) -> String {
+    let days = (secs / 86400) as i64;
+    let rem = secs % 86400;
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        (rem / 60) % 60,
+        rem % 60
+    )
+}
+
+// Simple glob match for --include / --exclude
+// * `*` matches any run of characters (including none)
+// * `?` matches a single character
+// * everything else matches itself
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    // position of the last '*' and the text position it is matched up to
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((spi, sti)) = star {
+            // let the '*' take one more character
+            pi = spi + 1;
+            ti = sti + 1;
+            star = Some((spi, sti + 1));
         } else {
-            return Err(OwError::General(
-                "No address given to listen on (--port)".to_string(),
-            ));
+            return false;
         }
-        Ok(())
     }
+    p[pi..].iter().all(|&c| c == '*')
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Arc;
+
+    // Stand-in owserver for tests
+    // * answers every query (on every connection) with the responses from `answer`
+    // * no responses means hang up (client sees an error)
+    // * returns the address to use as target
+    pub(super) fn mock_owserver<F>(answer: F) -> String
+    where
+        F: Fn(&OwQuery) -> Vec<OwResponse> + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let answer = Arc::new(answer);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else {
+                    break;
+                };
+                let answer = answer.clone();
+                thread::spawn(move || {
+                    while let Ok(query) = OwQuery::get(&mut stream, [0xFFu8; 16]) {
+                        let responses = answer(&query);
+                        if responses.is_empty() {
+                            return;
+                        }
+                        for mut response in responses {
+                            if response.send(&mut stream).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        address
+    }
+
+    // response with content and return code
+    pub(super) fn reply(ret: i32, content: &[u8]) -> OwResponse {
+        let mut response = OwResponse::new(0);
+        response.version = 0;
+        response.ret = ret;
+        response.payload = content.len() as i32;
+        response.size = content.len() as u32;
+        response.content = content.to_vec();
+        response
+    }
+
+    // path (without value) of a query
+    pub(super) fn query_path(query: &OwQuery) -> String {
+        let len = match query.mtype {
+            OwQuery::WRITE => (query.payload - query.size as i32) as usize,
+            _ => query.payload as usize,
+        };
+        String::from_utf8_lossy(&query.content[..len])
+            .trim_end_matches('\0')
+            .to_string()
+    }
+
+    pub(super) fn mock_client(address: &str) -> OwMessage {
+        let mut owc = OwMessage::new();
+        owc.stream.set_target(address);
+        owc
+    }
 
     #[test]
     fn new_client() {
@@ -673,6 +2680,57 @@ mod tests {
         assert_eq!(x, "48 65 6C 6C 6F");
     }
     #[test]
+    fn quiet_test() {
+        let mut owc = OwMessage::new();
+        assert_eq!(
+            owc.trouble_text("Trouble with path /10.FFFFFFFFFFFF"),
+            Some("Trouble with path /10.FFFFFFFFFFFF".to_string())
+        );
+        owc.quiet = true;
+        assert_eq!(owc.trouble_text("Trouble with path /10.FFFFFFFFFFFF"), None);
+    }
+
+    #[test]
+    fn trim_test() {
+        let mut owc = OwMessage::new();
+        let padded = b"   85.79  ".to_vec();
+        assert_eq!(owc.show_result(padded.clone()).unwrap(), "   85.79  ");
+        owc.trim = true;
+        assert_eq!(owc.show_result(padded.clone()).unwrap(), "85.79");
+        // hex is never trimmed
+        owc.hex = true;
+        assert_eq!(
+            owc.show_result(b" 1".to_vec()).unwrap(),
+            OwMessage::hex_string(b" 1")
+        );
+    }
+
+    #[test]
+    fn printable_fallback_test() {
+        let mut owc = OwMessage::new();
+        let v: Vec<u8> = vec![72, 105, 0xFF];
+        let x = owc.show_result(v.clone()).unwrap();
+        assert_eq!(x, "(not UTF-8) 48 69 FF");
+
+        // Strict
+        owc.strict = true;
+        assert!(owc.show_result(v).is_err());
+    }
+    #[test]
+    fn raw_test() {
+        let mut owc = OwMessage::new();
+        assert!(!owc.raw());
+        owc.raw = true;
+        assert!(owc.raw());
+
+        // raw output is the value bytes unchanged: no text conversion, no newline
+        let value = b"\x01\xffBytes \t,".to_vec();
+        assert_ne!(owc.show_result(value.clone()).unwrap().as_bytes(), value);
+        let mut out: Vec<u8> = Vec::new();
+        crate::console::write_bytes(&mut out, &value).unwrap();
+        assert_eq!(out, value);
+    }
+    #[test]
     fn bn_test() {
         let xs = vec![
             ("basename", "basename".to_string()),
@@ -681,10 +2739,1767 @@ mod tests {
             ("/dir/basename", "basename".to_string()),
             ("dir/basename/", "basename".to_string()),
             ("/root/dir/basename.2.3", "basename".to_string()),
+            ("/10.67C6697351FF/pages.ALL", "pages.ALL".to_string()),
+            ("/12.67C6697351FF/PIO.A", "PIO.A".to_string()),
+            ("/10.67C6697351FF", "10".to_string()),
+            ("/10.67C6697351FF.8D/", "10".to_string()),
+            ("/10.67C6697351FF/temperature", "temperature".to_string()),
+            ("/bus.0", "bus".to_string()),
         ];
         for x in xs {
             let s = OwMessage::basename(x.0);
             assert_eq!(s, x.1);
         }
     }
+
+    #[test]
+    fn address_case_test() {
+        let paths = [
+            (
+                "/10.abcdef012345/temperature",
+                "/10.ABCDEF012345/temperature",
+            ),
+            ("/1f.abcdef012345.8d/main/", "/1F.ABCDEF012345.8D/main/"),
+            (
+                "/bus.0/3a.abcdef012345/PIO.a",
+                "/bus.0/3A.ABCDEF012345/PIO.a",
+            ),
+            (
+                "/settings/units/temperature_scale",
+                "/settings/units/temperature_scale",
+            ),
+            ("", ""),
+        ];
+        for (lower, upper) in paths {
+            assert_eq!(OwMessage::address_case(lower, &AddressCase::UPPER), upper);
+            assert_eq!(OwMessage::address_case(upper, &AddressCase::LOWER), lower);
+            assert_eq!(
+                OwMessage::address_case(lower, &AddressCase::UNCHANGED),
+                lower
+            );
+        }
+    }
+
+    #[test]
+    fn read_map_test() {
+        let address = mock_owserver(|q| match query_path(q).as_str() {
+            "/10.67C6697351FF/temperature" => vec![reply(5, b"85.75")],
+            "/05.4AEC29CDBAAB/PIO" => vec![reply(1, b"1")],
+            _ => vec![reply(-2, b"")],
+        });
+        let mut owc = mock_client(&address);
+        let paths = [
+            "/10.67C6697351FF/temperature",
+            "/05.4AEC29CDBAAB/PIO",
+            "/05.4AEC29CDBAAB/temperature",
+        ];
+        let map = owc.read_map(&paths, true).unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["/10.67C6697351FF/temperature"], b"85.75".to_vec());
+        assert_eq!(map["/05.4AEC29CDBAAB/PIO"], b"1".to_vec());
+        assert!(!map.contains_key("/05.4AEC29CDBAAB/temperature"));
+
+        // error on failure
+        assert!(matches!(
+            owc.read_map(&paths, false),
+            Err(OwError::Server { code: -2, .. })
+        ));
+    }
+
+    #[test]
+    fn profile_test() {
+        let address = mock_owserver(|_| vec![reply(5, b"85.75")]);
+        let mut owc = mock_client(&address);
+        owc.profile = true;
+        assert_eq!(*owc.timing(), Timing::default());
+        owc.read("/10.67C6697351FF/temperature").unwrap();
+        let timing = owc.timing();
+        assert!(timing.connect.is_some());
+        assert!(timing.send.is_some());
+        assert!(timing.receive.is_some());
+    }
+
+    #[test]
+    fn take_stream_test() {
+        let address = mock_owserver(|_| vec![reply(5, b"85.75")]);
+        let mut owc = mock_client(&address);
+        assert!(owc.take_stream().is_none());
+
+        owc.read("/10.67C6697351FF/temperature").unwrap();
+        let mut stream = owc.take_stream().unwrap();
+        assert!(owc.stream.get().is_none());
+
+        // raw connection still usable
+        let mut query = owc.make_read("/10.67C6697351FF/temperature").unwrap();
+        query.send(&mut stream).unwrap();
+        let rcv = OwResponse::get(&mut stream).unwrap();
+        assert_eq!(rcv.content, b"85.75".to_vec());
+
+        // client reconnects
+        assert_eq!(
+            owc.read("/10.67C6697351FF/temperature").unwrap(),
+            b"85.75".to_vec()
+        );
+    }
+
+    #[test]
+    fn stale_pool_test() {
+        // owserver that answers one query per connection, then hangs up on the next
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let c = connections.clone();
+        thread::spawn(move || {
+            for mut stream in listener.incoming().map_while(Result::ok) {
+                c.fetch_add(1, Ordering::SeqCst);
+                thread::spawn(move || {
+                    if OwQuery::get(&mut stream, [0xFFu8; 16]).is_ok() {
+                        let _ = reply(5, b"85.75").send(&mut stream);
+                    }
+                    let _ = OwQuery::get(&mut stream, [0xFFu8; 16]);
+                });
+            }
+        });
+        let mut owc = mock_client(&address);
+        owc.stream.set_pool(true);
+        owc.make_flags();
+        let path = "/10.67C6697351FF/temperature";
+        assert_eq!(owc.read(path).unwrap(), b"85.75");
+        owc.close(); // pooled
+
+        // pooled connection fails, a new one is made
+        assert_eq!(owc.read(path).unwrap(), b"85.75");
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn unfinished_pool_test() {
+        // reply that never comes: connection isn't pooled
+        let address = mock_owserver(|q| match query_path(q).as_str() {
+            "/slow" => vec![],
+            _ => vec![reply(5, b"85.75")],
+        });
+        let mut owc = mock_client(&address);
+        owc.stream.set_pool(true);
+        owc.make_flags();
+        assert!(owc.read("/slow").is_err());
+        assert!(owc.stream.get().is_none());
+        owc.close();
+        assert_eq!(owc.read("/10.67C6697351FF/temperature").unwrap(), b"85.75");
+    }
+
+    #[test]
+    fn close_test() {
+        let address = mock_owserver(|_| vec![reply(5, b"85.75")]);
+        let mut owc = mock_client(&address);
+        owc.stream.set_persistence(true);
+        owc.read("/10.67C6697351FF/temperature").unwrap();
+        assert!(owc.stream.get().is_some());
+
+        owc.close();
+        assert!(owc.stream.get().is_none());
+
+        // reconnects
+        assert_eq!(
+            owc.read("/10.67C6697351FF/temperature").unwrap(),
+            b"85.75".to_vec()
+        );
+        assert!(owc.stream.get().is_some());
+    }
+
+    #[test]
+    fn reconnect_test() {
+        let address = mock_owserver(|_| vec![reply(5, b"85.75")]);
+        let mut owc = mock_client(&address);
+        owc.stream.set_persistence(true);
+        owc.reconnect().unwrap();
+        assert!(owc.stream.get().is_some());
+        assert_eq!(
+            owc.read("/10.67C6697351FF/temperature").unwrap(),
+            b"85.75".to_vec()
+        );
+        owc.reconnect().unwrap();
+        assert_eq!(
+            owc.read("/10.67C6697351FF/temperature").unwrap(),
+            b"85.75".to_vec()
+        );
+
+        // nothing listening
+        let closed = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string();
+        let mut owc = mock_client(&closed);
+        assert!(owc.reconnect().is_err());
+    }
+
+    #[test]
+    fn server_version_test() {
+        let address = mock_owserver(|q| {
+            let mut response = reply(0, b"3.2p4");
+            if query_path(q).starts_with("/system") {
+                response.version = 0x0001_0002;
+            }
+            vec![response]
+        });
+        let mut owc = mock_client(&address);
+        assert_eq!(owc.server_version(), None);
+
+        owc.read("/10.67C6697351FF/temperature").unwrap();
+        assert_eq!(owc.server_version(), Some(0));
+
+        assert_eq!(owc.server_build().unwrap(), "3.2p4");
+        assert_eq!(owc.server_version(), Some(0x0001_0002));
+    }
+
+    #[test]
+    fn empty_write_test() {
+        let path = "/05.4AEC29CDBAAB/PIO";
+        let owc = OwMessage::new();
+        let msg = owc.make_write(path, b"").unwrap();
+        assert_eq!(msg.mtype, OwQuery::WRITE);
+        assert_eq!(msg.size, 0);
+        // payload is just the nul-terminated path
+        assert_eq!(msg.payload, path.len() as i32 + 1);
+        assert_eq!(msg.content, b"/05.4AEC29CDBAAB/PIO\0");
+        assert_eq!(msg.payload, owc.make_read(path).unwrap().payload);
+
+        let address = mock_owserver(|q| match q.mtype {
+            OwQuery::WRITE if q.size == 0 => vec![reply(0, b"")],
+            _ => vec![reply(-22, b"")],
+        });
+        let mut owc = mock_client(&address);
+        owc.write(path, b"").unwrap();
+        assert!(owc.write(path, b"1").is_err());
+    }
+
+    #[test]
+    fn write_offset_test() {
+        let path = "/43.67C6697351FF/pages/page.0";
+        let mut owc = OwMessage::new();
+        assert_eq!(owc.make_write(path, b"DATA").unwrap().offset, 0);
+        owc.offset = 32;
+        let msg = owc.make_write(path, b"DATA").unwrap();
+        assert_eq!(msg.offset, 32);
+        assert_eq!(msg.size, 4);
+
+        let address = mock_owserver(|q| match q.offset {
+            32 => vec![reply(0, b"")],
+            _ => vec![reply(-22, b"")],
+        });
+        let mut owc = mock_client(&address);
+        assert!(owc.write(path, b"DATA").is_err());
+        owc.offset = 32;
+        owc.write(path, b"DATA").unwrap();
+    }
+
+    #[test]
+    fn write_preview_test() {
+        // anything connecting would be waiting here
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let mut owc = mock_client(&listener.local_addr().unwrap().to_string());
+        owc.offset = 16;
+
+        let lines = owc
+            .write_preview("/43.67C6697351FF/pages/page.0", b"AB")
+            .unwrap();
+        assert!(lines[0].starts_with("Dry run (not sent)"));
+        assert_eq!(lines[1], "WRITE /43.67C6697351FF/pages/page.0 => 41 42");
+        assert!(lines[3].ends_with("Size:2 Offset:16"));
+
+        assert!(owc.stream.get().is_none());
+        assert!(listener.accept().is_err());
+    }
+
+    #[test]
+    fn write_str_hex_test() {
+        let path = "/43.67C6697351FF/pages/page.0";
+        let address = mock_owserver(|q| {
+            let value = &q.content[q.content.len() - q.size as usize..];
+            match value {
+                b"Hello" => vec![reply(0, b"")],
+                _ => vec![reply(-22, b"")],
+            }
+        });
+        let mut owc = mock_client(&address);
+        owc.write_str(path, "Hello").unwrap();
+        owc.write_hex(path, "48656C6C6F").unwrap();
+        owc.write_hex(path, "48656c6c6f").unwrap();
+        assert!(matches!(
+            owc.write_str(path, "World"),
+            Err(OwError::Server { .. })
+        ));
+
+        // bad hex never reaches owserver
+        for bad in ["48656C6C6", "48656C6C6G", "é1"] {
+            assert!(
+                matches!(owc.write_hex(path, bad), Err(OwError::Numeric(_))),
+                "{}",
+                bad
+            );
+        }
+
+        // --hex setting doesn't matter
+        owc.hex = true;
+        owc.write_str(path, "Hello").unwrap();
+    }
+
+    #[test]
+    fn first_only_test() {
+        let address = mock_owserver(|q| match q.mtype {
+            OwQuery::DIR => vec![
+                reply(0, b"/10.67C6697351FF/address"),
+                reply(0, b"/10.67C6697351FF/temperature"),
+                reply(0, b"/10.67C6697351FF/type"),
+                reply(0, b""),
+            ],
+            OwQuery::DIRALL => vec![reply(
+                0,
+                b"/10.67C6697351FF/address,/10.67C6697351FF/temperature,/10.67C6697351FF/type",
+            )],
+            _ => vec![reply(0, b"85.75")],
+        });
+        let path = "/10.67C6697351FF";
+        let mut owc = mock_client(&address);
+        assert_eq!(owc.dir(path).unwrap().len(), 3);
+        assert_eq!(owc.dirall(path).unwrap().len(), 3);
+
+        owc.first_only = true;
+        assert_eq!(owc.dir(path).unwrap(), vec!["/10.67C6697351FF/address"]);
+        assert_eq!(owc.dirall(path).unwrap(), vec!["/10.67C6697351FF/address"]);
+
+        // first entry that gets past --prune
+        owc.prune = true;
+        assert_eq!(owc.dir(path).unwrap(), vec!["/10.67C6697351FF/temperature"]);
+        assert_eq!(
+            owc.dirall(path).unwrap(),
+            vec!["/10.67C6697351FF/temperature"]
+        );
+
+        // abandoned listing doesn't confuse a persistent connection
+        owc.stream.set_persistence(true);
+        owc.dir(path).unwrap();
+        assert_eq!(owc.read("/10.67C6697351FF/temperature").unwrap(), b"85.75");
+    }
+
+    #[test]
+    fn connect_test() {
+        // nothing listening
+        let dead = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = dead.local_addr().unwrap().to_string();
+        drop(dead);
+        let mut owc = mock_client(&address);
+        assert!(owc.connect().is_err());
+        assert!(!owc.is_connected());
+
+        let address = mock_owserver(|_| vec![reply(0, b"")]);
+        let mut owc = mock_client(&address);
+        assert!(!owc.is_connected());
+        owc.connect().unwrap();
+        assert!(owc.is_connected());
+        owc.close();
+        assert!(!owc.is_connected());
+    }
+
+    #[test]
+    fn present_test() {
+        let address = mock_owserver(|q| match query_path(q).as_str() {
+            "/10.67C6697351FF" => vec![reply(0, b"")],
+            "/10.FFFFFFFFFFFF" => vec![reply(-2, b"")],
+            _ => vec![reply(-5, b"")],
+        });
+        let mut owc = mock_client(&address);
+        assert!(owc.present("/10.67C6697351FF").unwrap());
+        assert!(!owc.present("/10.FFFFFFFFFFFF").unwrap());
+        assert!(owc.present("/bus.9").is_err());
+    }
+
+    #[test]
+    fn present_size_zero_test() {
+        let address = mock_owserver(|q| match q.mtype {
+            OwQuery::PRESENT | OwQuery::SIZE if q.size == 0 && q.offset == 0 => {
+                vec![reply(12, b"")]
+            }
+            _ => vec![],
+        });
+        let mut owc = mock_client(&address);
+        owc.offset = 10;
+        assert!(owc.present("/10.67C6697351FF/temperature").unwrap());
+        assert_eq!(owc.size("/10.67C6697351FF/temperature").unwrap(), 12);
+
+        let msg = owc.make_read("/10.67C6697351FF/temperature").unwrap();
+        assert_ne!(msg.size, 0);
+    }
+
+    #[test]
+    fn read_size_test() {
+        use parse_args::Parser;
+        let path = "/10.67C6697351FF/temperature";
+        let mut owc = OwMessage::new();
+        let default = owc.make_read(path).unwrap().size;
+        assert_eq!(default, 65536);
+
+        owc.set_read_size(12);
+        let msg = owc.make_read(path).unwrap();
+        assert_eq!(msg.size, 12);
+        assert_eq!(msg.offset, 0);
+
+        // --size 0 means server default
+        let mut owc = OwMessage::new();
+        parse_args::OwRead
+            .vector_line(&mut owc, vec!["--size", "0", "--offset", "4"])
+            .unwrap();
+        let msg = owc.make_read(path).unwrap();
+        assert_eq!(msg.size, default);
+        assert_eq!(msg.offset, 4);
+
+        parse_args::OwRead
+            .vector_line(&mut owc, vec!["--size", "12"])
+            .unwrap();
+        assert_eq!(owc.make_read(path).unwrap().size, 12);
+    }
+
+    #[test]
+    fn into_path_test() {
+        let address = mock_owserver(|q| match query_path(q).as_str() {
+            "/10.67C6697351FF/temperature" => vec![reply(0, b"85.75")],
+            _ => vec![reply(-2, b"")],
+        });
+        let mut owc = mock_client(&address);
+        let path = String::from("/10.67C6697351FF/temperature");
+        assert_eq!(owc.read("/10.67C6697351FF/temperature").unwrap(), b"85.75");
+        assert_eq!(owc.read(&path).unwrap(), b"85.75");
+        assert_eq!(owc.read(path).unwrap(), b"85.75");
+    }
+
+    #[test]
+    fn format_size_test() {
+        let sizes = [
+            (0, "0 B"),
+            (12, "12 B"),
+            (1023, "1023 B"),
+            (1024, "1.0 KiB"),
+            (2048, "2.0 KiB"),
+            (1048575, "1024.0 KiB"),
+            (1048576, "1.0 MiB"),
+            (1073741824, "1.0 GiB"),
+        ];
+        for (bytes, text) in sizes {
+            assert_eq!(OwMessage::format_size(bytes), text, "{}", bytes);
+        }
+    }
+
+    #[test]
+    fn flag_bits_test() {
+        let temperatures = [
+            (Temperature::CELSIUS, OwMessage::TEMPERATURE_C),
+            (Temperature::FARENHEIT, OwMessage::TEMPERATURE_F),
+            (Temperature::KELVIN, OwMessage::TEMPERATURE_K),
+            (Temperature::RANKINE, OwMessage::TEMPERATURE_R),
+            (Temperature::DEFAULT, OwMessage::TEMPERATURE_C),
+        ];
+        for (scale, bits) in temperatures {
+            assert_eq!(scale.bits(), bits, "{:?}", scale);
+        }
+        let pressures = [
+            (Pressure::MBAR, OwMessage::PRESSURE_MBAR),
+            (Pressure::MMHG, OwMessage::PRESSURE_MMHG),
+            (Pressure::INHG, OwMessage::PRESSURE_INHG),
+            (Pressure::ATM, OwMessage::PRESSURE_ATM),
+            (Pressure::PA, OwMessage::PRESSURE_PA),
+            (Pressure::PSI, OwMessage::PRESSURE_PSI),
+            (Pressure::DEFAULT, OwMessage::PRESSURE_MBAR),
+        ];
+        for (scale, bits) in pressures {
+            assert_eq!(scale.bits(), bits, "{:?}", scale);
+        }
+        let formats = [
+            (Format::FI, OwMessage::FORMAT_FI),
+            (Format::FdI, OwMessage::FORMAT_F_I),
+            (Format::FIC, OwMessage::FORMAT_FIC),
+            (Format::FIdC, OwMessage::FORMAT_FI_C),
+            (Format::FdIC, OwMessage::FORMAT_F_IC),
+            (Format::FdIdC, OwMessage::FORMAT_F_I_C),
+            (Format::DEFAULT, OwMessage::FORMAT_F_I),
+        ];
+        for (format, bits) in formats {
+            assert_eq!(format.bits(), bits, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn flag_string_test() {
+        let words = [
+            (0x0000_0000, "C mbar f.i"),
+            (0x0000_010A, "C mbar f.i net alias bus"),
+            (0x0001_0000, "F mbar f.i"),
+            (0x0003_0000, "R mbar f.i"),
+            (0x0012_0004, "K psi f.i persist"),
+            (0x000C_0020, "C inHg f.i uncache"),
+            (0x0504_0010, "C atm fic safe"),
+            (0x0301_0000, "F mbar f.ic"),
+        ];
+        for (flags, text) in words {
+            assert_eq!(OwMessage::flag_string(flags), text, "{:08X}", flags);
+        }
+    }
+
+    #[test]
+    fn leaf_name_test() {
+        let names = [
+            ("/10.67C6697351FF/temperature", "temperature"),
+            ("/10.67C6697351FF/errata/", "errata"),
+            ("/10.67C6697351FF", "10.67C6697351FF"),
+            ("/10.67C6697351FF/", "10.67C6697351FF"),
+            ("temperature", "temperature"),
+            ("/", ""),
+            ("", ""),
+        ];
+        for (entry, name) in names {
+            assert_eq!(OwMessage::leaf_name(entry), name, "{}", entry);
+        }
+
+        let address = mock_owserver(|q| match (q.mtype, query_path(q).as_str()) {
+            (OwQuery::DIRALL, "/10.67C6697351FF") => vec![reply(
+                0,
+                b"/10.67C6697351FF/address,/10.67C6697351FF/errata/,/10.67C6697351FF/temperature",
+            )],
+            _ => vec![],
+        });
+        let mut owc = mock_client(&address);
+        assert_eq!(
+            owc.dirall_names("/10.67C6697351FF").unwrap(),
+            vec!["address", "errata", "temperature"]
+        );
+    }
+
+    #[test]
+    fn watch_test() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let persistent = Arc::new(AtomicUsize::new(0));
+        let p = persistent.clone();
+        let address = mock_owserver(move |q| {
+            if q.flags & OwMessage::PERSISTENCE != 0 {
+                p.fetch_add(1, Ordering::SeqCst);
+            }
+            vec![reply(0, b"85.75")]
+        });
+        let mut owc = mock_client(&address);
+        let mut calls = 0;
+        owc.watch(
+            "/10.67C6697351FF/temperature",
+            Duration::from_millis(1),
+            |v| {
+                assert_eq!(v.unwrap(), b"85.75");
+                calls += 1;
+                calls < 3
+            },
+        );
+        assert_eq!(calls, 3);
+        assert_eq!(persistent.load(Ordering::SeqCst), 3);
+
+        // back to the original (non-persistent) state
+        assert!(!owc.stream.get_persistence());
+        assert_eq!(owc.flags & OwMessage::PERSISTENCE, 0);
+        assert!(owc.stream.get().is_none());
+    }
+
+    #[test]
+    fn listen_in_use_test() {
+        let taken = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = taken.local_addr().unwrap().to_string();
+        let mut owc = OwMessage::new();
+        owc.listener = Some(address.clone());
+        let result = owc.listen();
+        assert!(
+            matches!(result, Err(OwError::General(ref e)) if e.contains(&address) && e.contains("in use")),
+            "{:?}",
+            result
+        );
+
+        owc.listener = None;
+        assert!(matches!(owc.listen(), Err(OwError::General(e)) if e.contains("--port")));
+    }
+
+    #[test]
+    fn read_many_test() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let persistent = Arc::new(AtomicUsize::new(0));
+        let p = persistent.clone();
+        let address = mock_owserver(move |q| {
+            if q.flags & OwMessage::PERSISTENCE != 0 {
+                p.fetch_add(1, Ordering::SeqCst);
+            }
+            match query_path(q).as_str() {
+                "/10.67C6697351FF/temperature" => vec![reply(5, b"85.75")],
+                "/05.4AEC29CDBAAB/PIO" => vec![reply(1, b"1")],
+                "/26.8D3A2B000000/humidity" => vec![reply(4, b"41.2")],
+                _ => vec![reply(-2, b"")],
+            }
+        });
+        let mut owc = mock_client(&address);
+        let values = owc
+            .read_many(&[
+                "/26.8D3A2B000000/humidity",
+                "/10.67C6697351FF/temperature",
+                "/05.4AEC29CDBAAB/PIO",
+            ])
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![b"41.2".to_vec(), b"85.75".to_vec(), b"1".to_vec()]
+        );
+        assert_eq!(persistent.load(Ordering::SeqCst), 3);
+
+        // back to the original (non-persistent) state
+        assert!(!owc.stream.get_persistence());
+        assert_eq!(owc.flags & OwMessage::PERSISTENCE, 0);
+        assert!(owc.stream.get().is_none());
+        assert!(owc.read_many(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn min_interval_test() {
+        let address = mock_owserver(|_| vec![reply(0, b"85.75")]);
+        let mut owc = mock_client(&address);
+        owc.set_min_interval(Duration::from_millis(100));
+        let start = Instant::now();
+        owc.read_many(&[
+            "/10.67C6697351FF/temperature",
+            "/10.67C6697351FF/temperature",
+        ])
+        .unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        // a query after a long gap isn't delayed
+        thread::sleep(Duration::from_millis(100));
+        let start = Instant::now();
+        owc.read("/10.67C6697351FF/temperature").unwrap();
+        assert!(start.elapsed() < Duration::from_millis(100));
+    }
+
+    #[test]
+    fn workers_test() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        // slow owserver that records how many queries it has at once
+        let busy = Arc::new(AtomicUsize::new(0));
+        let most = Arc::new(AtomicUsize::new(0));
+        let (b, m) = (busy.clone(), most.clone());
+        let address = mock_owserver(move |_| {
+            m.fetch_max(b.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(50));
+            b.fetch_sub(1, Ordering::SeqCst);
+            vec![reply(0, b"85.75")]
+        });
+
+        // relay with 2 workers
+        let mut relay = mock_client(&address);
+        relay.workers = 2;
+        assert_eq!(relay.worker_count(), 2);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let relay_address = listener.local_addr().unwrap().to_string();
+        thread::spawn(move || relay.serve(listener, Arc::new(AtomicUsize::new(0))));
+
+        // more connections than workers
+        let clients: Vec<_> = (0..6)
+            .map(|_| {
+                let relay_address = relay_address.clone();
+                thread::spawn(move || {
+                    let mut owc = mock_client(&relay_address);
+                    owc.read("/10.67C6697351FF/temperature")
+                })
+            })
+            .collect();
+        for client in clients {
+            assert_eq!(client.join().unwrap().unwrap(), b"85.75");
+        }
+        assert!(most.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[test]
+    fn handled_count_test() {
+        let address = mock_owserver(|_| vec![reply(0, b"85.75")]);
+        let relay = mock_client(&address);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let relay_address = listener.local_addr().unwrap().to_string();
+        let handled = Arc::new(AtomicUsize::new(0));
+        let h = handled.clone();
+        thread::spawn(move || relay.serve(listener, h));
+
+        let mut owc = mock_client(&relay_address);
+        for n in 1..=3 {
+            owc.read("/10.67C6697351FF/temperature").unwrap();
+            // counted once the relay has finished with the connection
+            let start = Instant::now();
+            while handled.load(Ordering::Relaxed) < n && start.elapsed() < Duration::from_secs(2) {
+                thread::sleep(Duration::from_millis(5));
+            }
+            assert_eq!(handled.load(Ordering::Relaxed), n);
+        }
+    }
+
+    #[test]
+    fn protocol_version_test() {
+        let path = "/10.67C6697351FF/temperature";
+        let mut owc = OwMessage::new();
+        let msg = owc.make_read(path).unwrap();
+        assert_eq!(msg.version >> SERVERPROTOCOL_SHIFT, 0);
+
+        owc.set_protocol_version(3).unwrap();
+        for msg in [owc.make_read(path).unwrap(), owc.make_nop().unwrap()] {
+            assert_eq!(msg.version >> SERVERPROTOCOL_SHIFT, 3);
+            assert_eq!(msg.version & SERVERMESSAGE, SERVERMESSAGE);
+            assert_eq!(msg.version & SERVERTOKENS, 1);
+        }
+
+        assert!(owc.set_protocol_version(MAX_PROTOCOL + 1).is_err());
+        assert_eq!(owc.send_version, 3);
+    }
+
+    #[test]
+    fn read_fresh_test() {
+        let address = mock_owserver(|q| match q.flags & OwMessage::UNCACHED {
+            0 => vec![reply(0, b"cached")],
+            _ => vec![reply(0, b"fresh")],
+        });
+        let mut owc = mock_client(&address);
+        let flags = owc.flags();
+        assert_eq!(
+            owc.read_fresh("/10.67C6697351FF/temperature").unwrap(),
+            b"fresh"
+        );
+        assert_eq!(owc.flags(), flags);
+        assert_eq!(owc.read("/10.67C6697351FF/temperature").unwrap(), b"cached");
+
+        // restored after an error too
+        let mut owc = mock_client(&mock_owserver(|_| vec![]));
+        assert!(owc.read_fresh("/10.67C6697351FF/temperature").is_err());
+        assert_eq!(owc.flags(), flags);
+    }
+
+    #[test]
+    fn dir_filter_test() {
+        let address = mock_owserver(|q| match (q.mtype, query_path(q).as_str()) {
+            (OwQuery::DIRALL, "/") => vec![reply(
+                0,
+                b"/10.67C6697351FF,/28.4AEC29CDBAAB,/28.0000029CDBAA,/bus.0",
+            )],
+            _ => vec![],
+        });
+        let mut owc = mock_client(&address);
+        assert_eq!(
+            owc.dir_filter("/", |e| e.starts_with("/28.")).unwrap(),
+            vec!["/28.4AEC29CDBAAB", "/28.0000029CDBAA"]
+        );
+        assert!(owc.dir_filter("/", |_| false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn bus_info_test() {
+        let address = mock_owserver(|q| match (q.mtype, query_path(q).as_str()) {
+            (OwQuery::DIRALLSLASH, "/bus.0/interface/") => vec![reply(
+                0,
+                b"/bus.0/interface/settings/,/bus.0/interface/statistics/",
+            )],
+            (OwQuery::DIRALLSLASH, "/bus.0/interface/settings/") => vec![reply(
+                0,
+                b"/bus.0/interface/settings/name,/bus.0/interface/settings/usb/",
+            )],
+            (OwQuery::DIRALLSLASH, "/bus.0/interface/settings/usb/") => {
+                vec![reply(0, b"/bus.0/interface/settings/usb/datasampleoffset")]
+            }
+            (OwQuery::DIRALLSLASH, "/bus.0/interface/statistics/") => {
+                vec![reply(0, b"/bus.0/interface/statistics/errors")]
+            }
+            (OwQuery::READ, "/bus.0/interface/settings/name") => vec![reply(0, b"DS2490")],
+            (OwQuery::READ, "/bus.0/interface/settings/usb/datasampleoffset") => {
+                vec![reply(0, b"           8")]
+            }
+            (OwQuery::READ, "/bus.0/interface/statistics/errors") => {
+                vec![reply(0, b"           0")]
+            }
+            _ => vec![reply(-2, b"")],
+        });
+        let mut owc = mock_client(&address);
+        assert_eq!(
+            owc.bus_info(0).unwrap(),
+            vec![
+                ("settings/name".to_string(), "DS2490".to_string()),
+                ("settings/usb/datasampleoffset".to_string(), "8".to_string()),
+                ("statistics/errors".to_string(), "0".to_string()),
+            ]
+        );
+        assert!(owc.bus_info(1).is_err());
+    }
+
+    #[test]
+    fn probe_test() {
+        let root: Vec<String> = [
+            "/10.67C6697351FF",
+            "/bus.10",
+            "/bus.1",
+            "/bus.0",
+            "/bus.x",
+            "/settings",
+            "/bus.0/interface",
+        ]
+        .map(String::from)
+        .to_vec();
+        assert_eq!(
+            OwMessage::bus_paths(&root),
+            vec![
+                ("/bus.0".to_string(), 0),
+                ("/bus.1".to_string(), 1),
+                ("/bus.10".to_string(), 10),
+            ]
+        );
+
+        let address = mock_owserver(|q| match (q.mtype, query_path(q).as_str()) {
+            (OwQuery::DIRALL, "/") if q.flags & OwMessage::BUS_RET != 0 => vec![reply(
+                0,
+                b"/10.67C6697351FF,/bus.1,/bus.0,/uncached,/settings,/system",
+            )],
+            (OwQuery::DIRALL, "/") => vec![reply(0, b"/10.67C6697351FF,/settings,/system")],
+            (OwQuery::READ, "/system/adapter/name.0") => vec![reply(0, b"DS2490")],
+            (OwQuery::READ, "/system/adapter/name.1") => vec![reply(0, b"DS9490R")],
+            _ => vec![reply(-2, b"")],
+        });
+        let mut owc = mock_client(&address);
+        owc.bare = true;
+        owc.no_bus = true;
+        owc.make_flags();
+        assert_eq!(
+            owc.probe().unwrap(),
+            vec![
+                ("/bus.0".to_string(), "DS2490".to_string()),
+                ("/bus.1".to_string(), "DS9490R".to_string()),
+            ]
+        );
+        assert_eq!(owc.flags & OwMessage::BUS_RET, 0);
+    }
+
+    #[test]
+    fn get_result_test() {
+        let address = mock_owserver(|q| match (q.mtype, query_path(q).as_str()) {
+            (OwQuery::GET, "/10.67C6697351FF/temperature") => vec![reply(0, b"       85.75")],
+            (OwQuery::GET, "/10.67C6697351FF") => vec![reply(
+                0,
+                b"/10.67C6697351FF/address,/10.67C6697351FF/errata,/10.67C6697351FF/temperature\0",
+            )],
+            (OwQuery::GET, "/10.67C6697351FF/errata/die") => vec![reply(0, b"B7")],
+            (OwQuery::GET, "/system/adapter/address.0") => vec![reply(0, b"/dev/ttyUSB0")],
+            _ => vec![reply(-2, b"")],
+        });
+        let mut owc = mock_client(&address);
+        assert_eq!(
+            owc.get("/10.67C6697351FF/temperature").unwrap(),
+            GetResult::Value(b"       85.75".to_vec())
+        );
+        assert_eq!(
+            owc.get("/10.67C6697351FF/errata/die").unwrap(),
+            GetResult::Value(b"B7".to_vec())
+        );
+        assert_eq!(
+            owc.get("/10.67C6697351FF").unwrap(),
+            GetResult::Dir(vec![
+                "/10.67C6697351FF/address".to_string(),
+                "/10.67C6697351FF/errata".to_string(),
+                "/10.67C6697351FF/temperature".to_string(),
+            ])
+        );
+        // missing path is owserver's error
+        assert!(matches!(
+            owc.get("/05.4AEC29CDBAAB"),
+            Err(OwError::Server { code: -2, .. })
+        ));
+
+        // a value that is a path, not a child of the requested one
+        assert_eq!(
+            owc.get("/system/adapter/address.0").unwrap(),
+            GetResult::Value(b"/dev/ttyUSB0".to_vec())
+        );
+
+        let path = "/10.67C6697351FF";
+        assert!(OwMessage::is_listing(path, b"/10.67C6697351FF/address"));
+        assert!(OwMessage::is_listing(
+            "/10.67C6697351FF/",
+            b"/uncached/10.67C6697351FF/address,/uncached/10.67C6697351FF/errata/"
+        ));
+        assert!(OwMessage::is_listing("/", b"/10.67C6697351FF,/bus.0"));
+        assert!(!OwMessage::is_listing(
+            "/system/adapter/address.0",
+            b"/dev/ttyUSB0"
+        ));
+        assert!(!OwMessage::is_listing(path, b"/10.67C6697351FFX/address"));
+        assert!(!OwMessage::is_listing(path, b"/dev/null is not a sensor"));
+        assert!(!OwMessage::is_listing(path, &[0x2F, 0xFF]));
+    }
+
+    #[test]
+    fn units_test() {
+        assert_eq!(Temperature::FARENHEIT.symbol(), "°F");
+        assert_eq!(Temperature::DEFAULT.symbol(), "°C");
+        assert_eq!(Pressure::INHG.symbol(), "inHg");
+        assert_eq!(Pressure::DEFAULT.symbol(), "mbar");
+
+        let mut owc = OwMessage::new();
+        owc.temperature = Temperature::FARENHEIT;
+        owc.pressure = Pressure::PSI;
+        for (path, symbol) in [
+            ("/10.67C6697351FF/temperature", Some("°F")),
+            ("/28.0000000000AB/temperature12", Some("°F")),
+            ("/26.A10000000000/B1-R1-A/pressure", Some("psi")),
+            ("/10.67C6697351FF/TempHigh", Some("°F")),
+            ("/05.4AEC29CDBAAB/PIO", None),
+            ("/10.67C6697351FF/address", None),
+            ("/uncached/10.67C6697351FF/temperature", Some("°F")),
+            ("/structure/10/temperature", None),
+            ("/settings/units/temperature_scale", None),
+        ] {
+            assert_eq!(owc.unit_symbol(path), symbol, "{}", path);
+        }
+
+        let path = "/10.67C6697351FF/temperature";
+        assert_eq!(owc.show_units(path, "   85.75".to_string()), "   85.75");
+        owc.units = true;
+        assert_eq!(owc.show_units(path, "   85.75".to_string()), "   85.75 °F");
+        assert_eq!(owc.show_units("/05.4AEC29CDBAAB/PIO", "1".to_string()), "1");
+        owc.hex = true;
+        assert_eq!(owc.show_units(path, "41".to_string()), "41");
+    }
+
+    #[test]
+    fn cancel_test() {
+        let address = mock_owserver(|_| vec![reply(0, b"85.75")]);
+        let mut owc = mock_client(&address);
+        let stop = Arc::new(AtomicBool::new(false));
+        owc.set_cancel(stop.clone());
+
+        let s = stop.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            s.store(true, Ordering::Relaxed);
+        });
+        let start = Instant::now();
+        let mut results = Vec::new();
+        owc.watch(
+            "/10.67C6697351FF/temperature",
+            Duration::from_secs(10),
+            |v| {
+                results.push(v);
+                true
+            },
+        );
+        // stopped well before the second reading was due
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap(), b"85.75");
+        assert!(matches!(&results[1], Err(OwError::General(e)) if e == "cancelled"));
+    }
+
+    #[test]
+    fn cancel_listing_test() {
+        let address = mock_owserver(|q| match q.mtype {
+            OwQuery::DIR => vec![
+                reply(0, b"/10.67C6697351FF\0"),
+                reply(0, b"/05.4AEC29CDBAAB\0"),
+                reply(0, b""),
+            ],
+            _ => vec![reply(0, b"85.75")],
+        });
+        let mut owc = mock_client(&address);
+        owc.stream.set_persistence(true);
+        owc.make_flags();
+        let stop = Arc::new(AtomicBool::new(true));
+        owc.set_cancel(stop.clone());
+        assert!(matches!(owc.dir("/"), Err(OwError::General(e)) if e == "cancelled"));
+        assert!(owc.stream.get().is_none());
+
+        // next query gets its own reply, not the rest of the listing
+        stop.store(false, Ordering::Relaxed);
+        assert_eq!(owc.read("/10.67C6697351FF/temperature").unwrap(), b"85.75");
+    }
+
+    #[test]
+    fn read_cache_test() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let queries = Arc::new(AtomicUsize::new(0));
+        let q = queries.clone();
+        let address = mock_owserver(move |q_| {
+            q.fetch_add(1, Ordering::SeqCst);
+            match q_.mtype {
+                OwQuery::READ => vec![reply(0, b"85.75")],
+                _ => vec![reply(0, b"")],
+            }
+        });
+        let mut owc = mock_client(&address);
+        let path = "/10.67C6697351FF/temperature";
+
+        // miss, then hit
+        owc.set_cache_ttl(Some(Duration::from_millis(100)));
+        assert_eq!(owc.read(path).unwrap(), b"85.75");
+        assert_eq!(owc.read(path).unwrap(), b"85.75");
+        assert_eq!(queries.load(Ordering::SeqCst), 1);
+
+        // a write drops the cached value
+        owc.write(path, b"1").unwrap();
+        owc.read(path).unwrap();
+        assert_eq!(queries.load(Ordering::SeqCst), 3);
+
+        // explicit invalidation
+        owc.invalidate(path);
+        owc.read(path).unwrap();
+        owc.invalidate_all();
+        owc.read(path).unwrap();
+        assert_eq!(queries.load(Ordering::SeqCst), 5);
+
+        // expired
+        thread::sleep(Duration::from_millis(150));
+        owc.read(path).unwrap();
+        assert_eq!(queries.load(Ordering::SeqCst), 6);
+
+        // bypassed for --via-uncached
+        owc.uncached = true;
+        owc.read(path).unwrap();
+        assert_eq!(queries.load(Ordering::SeqCst), 7);
+        owc.uncached = false;
+
+        // bypassed by read_fresh while warm
+        owc.set_cache_ttl(Some(Duration::from_secs(60)));
+        owc.read(path).unwrap();
+        owc.read(path).unwrap();
+        assert_eq!(queries.load(Ordering::SeqCst), 8);
+        owc.read_fresh(path).unwrap();
+        assert_eq!(queries.load(Ordering::SeqCst), 9);
+
+        // other scale, size or offset is not the cached value
+        owc.temperature = Temperature::FARENHEIT;
+        owc.make_flags();
+        owc.read(path).unwrap();
+        owc.size = 2;
+        owc.read(path).unwrap();
+        owc.offset = 1;
+        owc.read(path).unwrap();
+        assert_eq!(queries.load(Ordering::SeqCst), 12);
+        owc.read(path).unwrap();
+        assert_eq!(queries.load(Ordering::SeqCst), 12);
+        // and a write drops them all
+        owc.write(path, b"1").unwrap();
+        owc.temperature = Temperature::CELSIUS;
+        owc.make_flags();
+        (owc.size, owc.offset) = (0, 0);
+        owc.read(path).unwrap();
+        assert_eq!(queries.load(Ordering::SeqCst), 14);
+    }
+
+    #[test]
+    fn read_cache_error_test() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let queries = Arc::new(AtomicUsize::new(0));
+        let q = queries.clone();
+        let address = mock_owserver(move |_| {
+            q.fetch_add(1, Ordering::SeqCst);
+            vec![reply(-5, b"")]
+        });
+        let mut owc = mock_client(&address);
+        owc.set_cache_ttl(Some(Duration::from_secs(60)));
+        assert!(owc.read("/10.67C6697351FF/temperature").is_err());
+        assert!(owc.read("/10.67C6697351FF/temperature").is_err());
+        assert_eq!(queries.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn dir_cache_test() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let queries = Arc::new(AtomicUsize::new(0));
+        let q = queries.clone();
+        let address = mock_owserver(move |_| {
+            q.fetch_add(1, Ordering::SeqCst);
+            vec![reply(0, b"/10.67C6697351FF,/05.4AEC29CDBAAB")]
+        });
+        let mut owc = mock_client(&address);
+        let listing = vec!["/10.67C6697351FF", "/05.4AEC29CDBAAB"];
+
+        // no cache by default
+        owc.dirall("/").unwrap();
+        owc.dirall("/").unwrap();
+        assert_eq!(queries.load(Ordering::SeqCst), 2);
+
+        // miss, then hit
+        owc.set_dir_cache_ttl(Some(Duration::from_millis(100)));
+        assert_eq!(owc.dirall("/").unwrap(), listing);
+        assert_eq!(owc.dirall("/").unwrap(), listing);
+        assert_eq!(queries.load(Ordering::SeqCst), 3);
+
+        // other path and the slash form are misses
+        owc.dirall("/10.67C6697351FF").unwrap();
+        owc.dirallslash("/").unwrap();
+        assert_eq!(queries.load(Ordering::SeqCst), 5);
+
+        // expired
+        thread::sleep(Duration::from_millis(150));
+        owc.dirall("/").unwrap();
+        assert_eq!(queries.load(Ordering::SeqCst), 6);
+
+        // bypassed for --via-uncached
+        owc.uncached = true;
+        owc.dirall("/").unwrap();
+        assert_eq!(queries.load(Ordering::SeqCst), 7);
+    }
+
+    #[test]
+    fn dir_cache_filter_test() {
+        let address = mock_owserver(|q| match (q.mtype, query_path(q).as_str()) {
+            (OwQuery::DIRALL, "/") if q.flags & OwMessage::BUS_RET != 0 => {
+                vec![reply(0, b"/10.67C6697351FF,/bus.0,/settings")]
+            }
+            (OwQuery::DIRALL, "/") => vec![reply(0, b"/10.67C6697351FF,/settings")],
+            (OwQuery::READ, "/system/adapter/name.0") => vec![reply(0, b"DS2490")],
+            _ => vec![reply(-2, b"")],
+        });
+        let mut owc = mock_client(&address);
+        owc.set_dir_cache_ttl(Some(Duration::from_secs(60)));
+
+        // cached --bare listing doesn't hide the buses from probe
+        owc.bare = true;
+        owc.no_bus = true;
+        owc.make_flags();
+        let bare = owc.dirall("/").unwrap();
+        assert_eq!(
+            owc.probe().unwrap(),
+            vec![("/bus.0".to_string(), "DS2490".to_string())]
+        );
+        assert_eq!(owc.dirall("/").unwrap(), bare);
+
+        // nor does the cached listing skip the filters
+        owc.bare = false;
+        assert_eq!(
+            owc.dirall("/").unwrap(),
+            vec!["/10.67C6697351FF", "/settings"]
+        );
+    }
+
+    #[test]
+    fn bare_no_bus_test() {
+        let address = mock_owserver(|q| match q.flags & OwMessage::BUS_RET {
+            0 => vec![reply(0, b"/10.67C6697351FF,/settings,/system")],
+            _ => vec![reply(
+                0,
+                b"/10.67C6697351FF,/bus.0,/uncached,/settings,/system",
+            )],
+        });
+        let mut owc = mock_client(&address);
+        let cases = [
+            (
+                false,
+                false,
+                true,
+                "/10.67C6697351FF,/bus.0,/uncached,/settings,/system",
+            ),
+            (false, true, false, "/10.67C6697351FF,/settings,/system"),
+            (true, false, true, "/10.67C6697351FF"),
+            (true, true, false, "/10.67C6697351FF"),
+        ];
+        for (bare, no_bus, bus_ret, listing) in cases {
+            owc.bare = bare;
+            owc.no_bus = no_bus;
+            owc.make_flags();
+            assert_eq!(owc.flags & OwMessage::BUS_RET != 0, bus_ret);
+            assert_eq!(owc.dirall("/").unwrap().join(","), listing);
+        }
+    }
+
+    #[test]
+    fn bare_test() {
+        let address = mock_owserver(|_| {
+            vec![reply(
+                0,
+                b"/10.67C6697351FF,/28.0000000000AB.CD,/kitchen,/bus.0,/settings,/statistics",
+            )]
+        });
+        let mut owc = mock_client(&address);
+        owc.bare = true;
+        // bad check byte and alias names are still devices
+        assert_eq!(
+            owc.dirall("/").unwrap(),
+            vec!["/10.67C6697351FF", "/28.0000000000AB.CD", "/kitchen"]
+        );
+    }
+
+    #[test]
+    fn dir_present_test() {
+        let address = mock_owserver(|q| match (q.mtype, query_path(q).as_str()) {
+            (OwQuery::DIRALL, "/") => vec![reply(0, b"/10.67C6697351FF,/05.4AEC29CDBAAB")],
+            (OwQuery::PRESENT, "/10.67C6697351FF") => vec![reply(0, b"")],
+            (OwQuery::PRESENT, "/05.4AEC29CDBAAB") => vec![reply(-2, b"")],
+            _ => vec![],
+        });
+        let mut owc = mock_client(&address);
+        assert_eq!(
+            owc.dir_present("/").unwrap(),
+            vec![
+                ("/10.67C6697351FF".to_string(), true),
+                ("/05.4AEC29CDBAAB".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn temperature_names() {
+        let names = [
+            ("celsius", Temperature::CELSIUS),
+            ("c", Temperature::CELSIUS),
+            ("Celsius", Temperature::CELSIUS),
+            ("C", Temperature::CELSIUS),
+            ("fahrenheit", Temperature::FARENHEIT),
+            ("farenheit", Temperature::FARENHEIT),
+            ("f", Temperature::FARENHEIT),
+            ("FAHRENHEIT", Temperature::FARENHEIT),
+            ("F", Temperature::FARENHEIT),
+            ("kelvin", Temperature::KELVIN),
+            ("k", Temperature::KELVIN),
+            ("Kelvin", Temperature::KELVIN),
+            ("K", Temperature::KELVIN),
+            ("rankine", Temperature::RANKINE),
+            ("r", Temperature::RANKINE),
+            ("Rankine", Temperature::RANKINE),
+            ("R", Temperature::RANKINE),
+        ];
+        for (name, t) in names {
+            assert_eq!(Temperature::try_from(name).unwrap(), t, "{}", name);
+        }
+        assert!(matches!(
+            Temperature::try_from("celcius"),
+            Err(OwError::Input(_))
+        ));
+    }
+
+    #[test]
+    fn pressure_names() {
+        let names = [
+            ("mmhg", Pressure::MMHG),
+            ("mmHg", Pressure::MMHG),
+            ("inhg", Pressure::INHG),
+            ("inHg", Pressure::INHG),
+            ("mbar", Pressure::MBAR),
+            ("mBar", Pressure::MBAR),
+            ("atm", Pressure::ATM),
+            ("ATM", Pressure::ATM),
+            ("pa", Pressure::PA),
+            ("Pa", Pressure::PA),
+            ("psi", Pressure::PSI),
+            ("PSI", Pressure::PSI),
+        ];
+        for (name, p) in names {
+            assert_eq!(Pressure::try_from(name).unwrap(), p, "{}", name);
+        }
+        assert!(matches!(Pressure::try_from("bar"), Err(OwError::Input(_))));
+    }
+
+    #[test]
+    fn read_and_format_test() {
+        let address = mock_owserver(|q| match query_path(q).as_str() {
+            "/10.67C6697351FF/temperature" => vec![reply(0, b"     85.7961")],
+            "/structure/10/temperature" => vec![reply(0, b"t,000000,000001,ro,000012,v,")],
+            "/26.4AEC29CDBAAB/humidity" => vec![reply(0, b"     45.125")],
+            "/structure/26/humidity" => vec![reply(0, b"f,000000,000001,ro,000012,v,")],
+            "/26.4AEC29CDBAAB/VAD" => vec![reply(0, b"      4.98")],
+            "/structure/26/VAD" => vec![reply(0, b"f,000000,000001,ro,000012,v,")],
+            "/26.4AEC29CDBAAB/type" => vec![reply(0, b"DS2438")],
+            "/structure/26/type" => vec![reply(0, b"a,000000,000001,ro,000032,f,")],
+            _ => vec![],
+        });
+        let mut owc = mock_client(&address);
+        assert_eq!(
+            owc.read_and_format("/10.67C6697351FF/temperature").unwrap(),
+            "85.7961 °C"
+        );
+        assert_eq!(
+            owc.read_and_format("/26.4AEC29CDBAAB/humidity").unwrap(),
+            "45.125 %RH"
+        );
+        assert_eq!(
+            owc.read_and_format("/26.4AEC29CDBAAB/VAD").unwrap(),
+            "4.98 V"
+        );
+        assert_eq!(
+            owc.read_and_format("/26.4AEC29CDBAAB/type").unwrap(),
+            "DS2438"
+        );
+
+        owc.temperature = Temperature::FARENHEIT;
+        assert_eq!(
+            owc.read_and_format("/10.67C6697351FF/temperature").unwrap(),
+            "85.7961 °F"
+        );
+    }
+
+    #[test]
+    fn structure_path_test() {
+        assert_eq!(
+            OwMessage::structure_path("/10.67C6697351FF/temperature"),
+            Some("/structure/10/temperature".to_string())
+        );
+        assert_eq!(
+            OwMessage::structure_path("/uncached/26.4AEC29CDBAAB/humidity"),
+            Some("/structure/26/humidity".to_string())
+        );
+        assert_eq!(OwMessage::structure_path("/statistics/errors"), None);
+        assert_eq!(OwMessage::structure_path("/10.67C6697351FF"), None);
+    }
+
+    #[test]
+    fn via_uncached_test() {
+        let mut owc = OwMessage::new();
+        assert_eq!(
+            owc.query_path("/10.67C6697351FF/temperature"),
+            "/10.67C6697351FF/temperature"
+        );
+
+        owc.uncached = true;
+        assert_eq!(
+            owc.query_path("/10.67C6697351FF/temperature"),
+            "/uncached/10.67C6697351FF/temperature"
+        );
+        assert_eq!(
+            owc.query_path("10.67C6697351FF/temperature"),
+            "/uncached/10.67C6697351FF/temperature"
+        );
+        assert_eq!(
+            owc.query_path("/uncached/10.67C6697351FF/temperature"),
+            "/uncached/10.67C6697351FF/temperature"
+        );
+        assert_eq!(owc.query_path("/uncached"), "/uncached");
+        assert_eq!(owc.query_path("/"), "/uncached");
+        assert_eq!(owc.query_path("/uncachedx"), "/uncached/uncachedx");
+
+        // every message type
+        let path = "/10.67C6697351FF/temperature";
+        for msg in [
+            owc.make_read(path).unwrap(),
+            owc.make_write(path, b"1").unwrap(),
+            owc.make_present(path).unwrap(),
+            owc.make_get(path).unwrap(),
+        ] {
+            assert_eq!(
+                query_path(&msg).trim_end_matches('\0'),
+                "/uncached/10.67C6697351FF/temperature"
+            );
+        }
+    }
+
+    #[test]
+    fn glob_test() {
+        assert!(glob_match("temp*", "temperature"));
+        assert!(glob_match("*temp", "latesttemp"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("1?", "10"));
+        assert!(glob_match("t*p*e", "temperature"));
+        assert!(!glob_match("1?", "1"));
+        assert!(!glob_match("temp", "temperature"));
+        assert!(!glob_match("*high", "templow"));
+    }
+
+    #[test]
+    fn include_exclude_test() {
+        let dir = "/10.67C6697351FF/address,/10.67C6697351FF/latesttemp,/10.67C6697351FF/temperature,/10.67C6697351FF/temphigh,/10.67C6697351FF/templow,/10.67C6697351FF/type";
+        let mut owc = OwMessage::new();
+
+        owc.include = vec!["temp*".to_string(), "type".to_string()];
+        let d = owc.dirboth(&mut dir.as_bytes().to_vec()).unwrap();
+        assert_eq!(
+            d,
+            vec![
+                "/10.67C6697351FF/temperature",
+                "/10.67C6697351FF/temphigh",
+                "/10.67C6697351FF/templow",
+                "/10.67C6697351FF/type",
+            ]
+        );
+
+        owc.exclude = vec!["*low".to_string(), "*high".to_string()];
+        let d = owc.dirboth(&mut dir.as_bytes().to_vec()).unwrap();
+        assert_eq!(
+            d,
+            vec!["/10.67C6697351FF/temperature", "/10.67C6697351FF/type",]
+        );
+
+        owc.include.clear();
+        let d = owc.dirboth(&mut dir.as_bytes().to_vec()).unwrap();
+        assert_eq!(d.len(), 4);
+
+        // device families at the root
+        let root = "/10.67C6697351FF,/28.4AEC29CDBAAB,/05.4AEC29CDBAAB,/bus.0,/settings";
+        let mut owc = OwMessage::new();
+        owc.include = vec!["10".to_string(), "28".to_string()];
+        let d = owc.dirboth(&mut root.as_bytes().to_vec()).unwrap();
+        assert_eq!(d, vec!["/10.67C6697351FF", "/28.4AEC29CDBAAB"]);
+    }
+
+    #[test]
+    fn simultaneous_test() {
+        let address = mock_owserver(|q| {
+            let path = query_path(q);
+            let value = &q.content[(q.payload as usize - q.size as usize)..q.payload as usize];
+            match (q.mtype, path.as_str(), value) {
+                (OwQuery::WRITE, "/simultaneous/temperature", b"1") => vec![reply(0, b"")],
+                (OwQuery::WRITE, "/simultaneous/voltage", b"1") => vec![reply(0, b"")],
+                _ => vec![reply(-22, b"")],
+            }
+        });
+        let mut owc = mock_client(&address);
+        owc.simultaneous_temperature().unwrap();
+        owc.simultaneous_voltage().unwrap();
+
+        let msg = owc.make_write("/simultaneous/temperature", b"1").unwrap();
+        assert_eq!(msg.mtype, OwQuery::WRITE);
+        assert_eq!(msg.size, 1);
+        assert_eq!(query_path(&msg), "/simultaneous/temperature");
+    }
+
+    #[test]
+    fn read_all_temperatures_test() {
+        let triggered = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let t = triggered.clone();
+        let address = mock_owserver(move |q| {
+            use std::sync::atomic::Ordering;
+            match (q.mtype, query_path(q).as_str()) {
+                (OwQuery::WRITE, "/simultaneous/temperature") => {
+                    t.store(true, Ordering::SeqCst);
+                    vec![reply(0, b"")]
+                }
+                (OwQuery::DIRALL, "/") => vec![reply(
+                    0,
+                    b"/10.67C6697351FF,/28.4AEC29CDBAAB,/05.4AEC29CDBAAB,/22.0123456789AB,/3B0123456789AB,/bus.0",
+                )],
+                // readings only valid after the conversion
+                (OwQuery::READ, _) if !t.load(Ordering::SeqCst) => vec![reply(-5, b"")],
+                (OwQuery::READ, "/10.67C6697351FF/latesttemp") => {
+                    let f = q.flags & OwMessage::TEMPERATURE_MASK;
+                    match f {
+                        OwMessage::TEMPERATURE_F => vec![reply(0, b"     186.35")],
+                        _ => vec![reply(0, b"      85.75")],
+                    }
+                }
+                (OwQuery::READ, "/28.4AEC29CDBAAB/latesttemp") => vec![reply(0, b"      70.7")],
+                // unreadable sensor and another address format
+                (OwQuery::READ, "/22.0123456789AB/latesttemp") => vec![reply(0, b"junk")],
+                (OwQuery::READ, "/3B0123456789AB/latesttemp") => vec![reply(0, b"      68.0")],
+                _ => vec![],
+            }
+        });
+        let mut owc = mock_client(&address);
+        owc.conversion = Duration::ZERO;
+        let temps = owc.read_all_temperatures(Temperature::FARENHEIT).unwrap();
+        assert_eq!(
+            temps,
+            vec![
+                ("/10.67C6697351FF".to_string(), 186.35),
+                ("/28.4AEC29CDBAAB".to_string(), 70.7),
+                ("/3B0123456789AB".to_string(), 68.0),
+            ]
+        );
+        // scale and flags restored
+        assert_eq!(owc.temperature, Temperature::DEFAULT);
+        assert_eq!(owc.flags & OwMessage::TEMPERATURE_MASK, 0);
+    }
+
+    #[test]
+    fn user_flag_test() {
+        let mut owc = OwMessage::new();
+        owc.set_flag(OwMessage::UNCACHED | OwMessage::ALIAS);
+        owc.make_flags();
+        assert_eq!(owc.flags() & OwMessage::UNCACHED, OwMessage::UNCACHED);
+        assert_eq!(owc.flags() & OwMessage::ALIAS, OwMessage::ALIAS);
+
+        owc.clear_flag(OwMessage::ALIAS);
+        owc.make_flags();
+        assert_eq!(owc.flags() & OwMessage::ALIAS, 0);
+        assert_eq!(owc.flags() & OwMessage::UNCACHED, OwMessage::UNCACHED);
+
+        // managed bits follow the configuration
+        owc.set_flag(OwMessage::TEMPERATURE_K);
+        assert_eq!(
+            owc.flags() & OwMessage::TEMPERATURE_MASK,
+            OwMessage::TEMPERATURE_K
+        );
+        owc.make_flags();
+        assert_eq!(
+            owc.flags() & OwMessage::TEMPERATURE_MASK,
+            OwMessage::TEMPERATURE_C
+        );
+    }
+
+    #[test]
+    fn write_once_test() {
+        let writes = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let w = writes.clone();
+        let address = mock_owserver(move |q| {
+            if q.mtype == OwQuery::WRITE {
+                w.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            }
+            match query_path(q).as_str() {
+                "/05.4AEC29CDBAAB/PIO" => vec![reply(0, b"")],
+                _ => vec![reply(-2, b"")],
+            }
+        });
+        let mut owc = mock_client(&address);
+        let count = || writes.load(std::sync::atomic::Ordering::SeqCst);
+
+        assert!(owc.write_once(17, "/05.4AEC29CDBAAB/PIO", b"1").unwrap());
+        assert!(!owc.write_once(17, "/05.4AEC29CDBAAB/PIO", b"1").unwrap());
+        assert_eq!(count(), 1);
+        assert!(owc.write_once(18, "/05.4AEC29CDBAAB/PIO", b"0").unwrap());
+        assert_eq!(count(), 2);
+
+        // failed write can be retried with the same nonce
+        assert!(owc.write_once(19, "/05.FFFFFFFFFFFF/PIO", b"1").is_err());
+        assert!(owc.write_once(19, "/05.FFFFFFFFFFFF/PIO", b"1").is_err());
+        assert_eq!(count(), 4);
+
+        // only recent nonces are remembered
+        for nonce in 100..100 + OwMessage::WRITE_NONCES as u64 {
+            owc.write_once(nonce, "/05.4AEC29CDBAAB/PIO", b"1").unwrap();
+        }
+        assert!(owc.write_once(17, "/05.4AEC29CDBAAB/PIO", b"1").unwrap());
+    }
+
+    #[test]
+    fn server_info_test() {
+        let address = mock_owserver(|q| {
+            let mut response = match (q.mtype, query_path(q).as_str()) {
+                (OwQuery::READ, "/system/process/pid") => reply(0, b"        4021"),
+                (OwQuery::DIRALL, "/") => reply(
+                    0,
+                    b"/10.67C6697351FF,/05.4AEC29CDBAAB,/bus.0,/uncached,/settings,/system",
+                ),
+                _ => reply(-2, b""),
+            };
+            response.version = 0x0001_0000;
+            vec![response]
+        });
+        let mut owc = mock_client(&address);
+        assert_eq!(
+            owc.server_info().unwrap(),
+            ServerInfo {
+                version: Some(0x0001_0000),
+                pid: 4021,
+                devices: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn self_test_test() {
+        // old owserver without /system/process/version, and no PRESENT
+        let address = mock_owserver(|q| match (q.mtype, query_path(q).as_str()) {
+            (OwQuery::NOP, _) => vec![reply(0, b"")],
+            (OwQuery::DIRALL, "/") => vec![reply(0, b"/10.67C6697351FF,/bus.0,/settings")],
+            (OwQuery::READ, _) => vec![reply(-2, b"")],
+            _ => vec![reply(-42, b"")],
+        });
+        let mut owc = mock_client(&address);
+        let report = owc.self_test().unwrap();
+        assert!(report.ping);
+        assert!(report.root_dir);
+        assert!(!report.version);
+        assert!(!report.present);
+        assert!(!report.healthy());
+        assert_eq!(report.problems.len(), 2);
+        assert_eq!(
+            report.problems[1],
+            "present: owserver: No message of desired type"
+        );
+
+        // nothing works without a server
+        let mut owc = mock_client(&mock_owserver(|_| vec![]));
+        let report = owc.self_test().unwrap();
+        assert!(!report.ping && !report.root_dir && !report.version && !report.present);
+        assert_eq!(report.problems.len(), 4);
+    }
+
+    #[test]
+    fn read_with_time_test() {
+        let address = mock_owserver(|_| vec![reply(5, b"85.75")]);
+        let mut owc = mock_client(&address);
+        let before = SystemTime::now();
+        let (time, v) = owc.read_with_time("/10.67C6697351FF/temperature").unwrap();
+        assert_eq!(v, b"85.75".to_vec());
+        assert!(time >= before);
+        assert!(time <= SystemTime::now());
+    }
+
+    #[test]
+    fn stamp_test() {
+        let mut owc = OwMessage::new();
+        let time = UNIX_EPOCH + Duration::from_millis(1_748_781_005_250);
+        assert_eq!(owc.stamp(time), "");
+
+        owc.timestamp = true;
+        assert_eq!(owc.stamp(time), "2025-06-01T12:30:05Z ");
+        assert_eq!(owc.stamp(UNIX_EPOCH), "1970-01-01T00:00:00Z ");
+        assert_eq!(
+            owc.stamp(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+            "2000-02-29T00:00:00Z "
+        );
+
+        owc.epoch = true;
+        assert_eq!(owc.stamp(time), "1748781005.250 ");
+    }
+
+    #[test]
+    fn max_packets_test() {
+        // never sends the terminating empty packet
+        let address = mock_owserver(|q| match q.mtype {
+            OwQuery::DIR => (0..20).map(|_| reply(0, b"/10.67C6697351FF\0")).collect(),
+            _ => vec![],
+        });
+        let mut owc = mock_client(&address);
+        owc.set_max_packets(5);
+        match owc.dir("/") {
+            Err(OwError::General(e)) => assert!(e.contains("5 continuation")),
+            other => panic!("Expected packet limit error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn read_string_test() {
+        let address = mock_owserver(|q| match query_path(q).as_str() {
+            "/system/adapter/name.0" => vec![reply(0, b"DS9490R")],
+            _ => vec![reply(0, &[0x44, 0x53, 0xFF])],
+        });
+        let mut owc = mock_client(&address);
+        owc.hex = true;
+        assert_eq!(
+            owc.read_string("/system/adapter/name.0").unwrap(),
+            "DS9490R"
+        );
+        assert!(matches!(
+            owc.read_string("/10.67C6697351FF/scratchpad"),
+            Err(OwError::Text(_))
+        ));
+    }
+
+    #[test]
+    fn read_sized_test() {
+        let address = mock_owserver(|q| match (q.mtype, query_path(q).as_str()) {
+            (OwQuery::SIZE, _) => vec![reply(12, b"")],
+            (OwQuery::READ, "/10.67C6697351FF/temperature") => vec![reply(0, b"     85.7961")],
+            (OwQuery::READ, _) => vec![reply(0, b"  85.")],
+            _ => vec![],
+        });
+        let mut owc = mock_client(&address);
+        assert_eq!(
+            owc.read_sized("/10.67C6697351FF/temperature").unwrap(),
+            b"     85.7961".to_vec()
+        );
+        assert_eq!(
+            owc.read_sized("/10.4AEC29CDBAAB/temperature").unwrap(),
+            b"  85.".to_vec()
+        );
+
+        owc.strict = true;
+        assert!(owc.read_sized("/10.67C6697351FF/temperature").is_ok());
+        match owc.read_sized("/10.4AEC29CDBAAB/temperature") {
+            Err(OwError::Output(e)) => assert!(e.contains("5 of 12 bytes"), "{}", e),
+            other => panic!("Expected truncation error, got {:?}", other),
+        }
+
+        // --size (or --offset) shorter than the file is not a short read
+        owc.size = 5;
+        assert!(owc.read_sized("/10.4AEC29CDBAAB/temperature").is_ok());
+        owc.size = 0;
+        owc.offset = 7;
+        assert!(owc.read_sized("/10.4AEC29CDBAAB/temperature").is_ok());
+    }
+
+    #[test]
+    fn read_checked_test() {
+        let address = mock_owserver(|q| match (q.mtype, query_path(q).as_str()) {
+            (OwQuery::PRESENT, "/10.67C6697351FF/temperature") => vec![reply(0, b"")],
+            (OwQuery::PRESENT, _) => vec![reply(-2, b"")],
+            (OwQuery::READ, "/10.67C6697351FF/temperature") => vec![reply(0, b"85.75")],
+            // a raw protocol failure for anything else
+            _ => vec![],
+        });
+        let mut owc = mock_client(&address);
+        assert_eq!(
+            owc.read_checked("/10.67C6697351FF/temperature").unwrap(),
+            b"85.75".to_vec()
+        );
+        match owc.read_checked("/10.FFFFFFFFFFFF/temperature") {
+            Err(OwError::General(e)) => assert!(e.starts_with("device not present")),
+            other => panic!("Expected not present error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn scan_test() {
+        let _logs = crate::logging::tests::capture();
+
+        let address = mock_owserver(|q| match (q.mtype, query_path(q).as_str()) {
+            (OwQuery::DIRALL, "/") => vec![reply(
+                0,
+                concat!(
+                    "/10.67C6697351FF.8D,/bus.0,/05.4AEC29CDBAAB,",
+                    "/28.0000000000AB.CD,/settings,/system"
+                )
+                .as_bytes(),
+            )],
+            _ => vec![reply(-2, b"")],
+        });
+        let mut owc = mock_client(&address);
+        assert_eq!(
+            owc.scan().unwrap(),
+            vec![
+                RomId::new([0x10, 0x67, 0xC6, 0x69, 0x73, 0x51, 0xFF]),
+                RomId::new([0x05, 0x4A, 0xEC, 0x29, 0xCD, 0xBA, 0xAB]),
+            ]
+        );
+        assert!(!owc.bare);
+        assert!(crate::logging::tests::logged(log::Level::Warn, |text| {
+            text.contains("/28.0000000000AB.CD")
+        }));
+    }
+
+    #[test]
+    fn aliases_test() {
+        let address = mock_owserver(|q| match (q.mtype, query_path(q).as_str()) {
+            (OwQuery::DIRALL, "/") => vec![reply(
+                0,
+                b"/10.67C6697351FF,/05.4AEC29CDBAAB,/28.0000000000AB,/bus.0,/settings",
+            )],
+            (OwQuery::READ, "/10.67C6697351FF/alias") => vec![reply(0, b"boiler")],
+            (OwQuery::READ, "/05.4AEC29CDBAAB/alias") => vec![reply(0, b"")],
+            (OwQuery::READ, "/28.0000000000AB/alias") => vec![reply(0, b" attic ")],
+            _ => vec![reply(-2, b"")],
+        });
+        let mut owc = mock_client(&address);
+        let boiler = RomId::new([0x10, 0x67, 0xC6, 0x69, 0x73, 0x51, 0xFF]);
+        let attic = RomId::new([0x28, 0, 0, 0, 0, 0, 0xAB]);
+        let aliases = owc.aliases().unwrap();
+        assert_eq!(
+            aliases,
+            vec![(boiler, "boiler".to_string()), (attic, "attic".to_string())]
+        );
+
+        let devices = OwMessage::alias_lookup(&aliases);
+        assert_eq!(devices.get("attic"), Some(&attic));
+        assert_eq!(devices.get("cellar"), None);
+    }
+
+    #[test]
+    fn log_connect_test() {
+        let _logs = crate::logging::tests::capture();
+
+        let address = mock_owserver(|_| vec![reply(5, b"85.75")]);
+        let mut owc = mock_client(&address);
+        owc.read("/10.67C6697351FF/temperature").unwrap();
+
+        let expect = format!("Connected to owserver {}", address);
+        assert!(crate::logging::tests::logged(log::Level::Debug, |text| {
+            text == expect
+        }));
+    }
 }
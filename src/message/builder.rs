@@ -0,0 +1,200 @@
+//! **owrust** Rust library interfaces with owserver to use 1-wire devices
+//!
+//! This is a tool in the 1-wire file system **OWFS**
+//!
+//! This library is the central part of **owrust** -- the _rust language_ OWFS programs
+//! * **OWFS** [documentation](https://owfs.org) and [code](https://github.com/owfs/owfs)
+//! * **owrust** [repository](https://github.com/alfille/owrust)
+//!
+//! ## PURPOSE
+//! OwMessageBuilder configures an OwMessage from library code
+//! * chainable settings
+//! * no command line parsing needed
+//! * **build** computes the owserver flags
+//!
+//! ## EXAMPLES
+//! ```
+//! use owrust::message::{OwMessage, Temperature};
+//! use std::time::Duration;
+//!
+//! let owserver = OwMessage::builder()
+//!     .server("localhost:4304")
+//!     .temperature(Temperature::FARENHEIT)
+//!     .persist(true)
+//!     .timeout(Duration::from_secs(2))
+//!     .build();
+//! ```
+
+// owrust project
+// https://github.com/alfille/owrust
+//
+// This is a Rust version of my C owfs code for talking to 1-wire devices via owserver
+// Basically owserver can talk to the physical devices, and provides network access via my "owserver protocol"
+//
+// MIT Licence
+// {c} 2025 Paul H Alfille
+
+use std::time::Duration;
+
+use crate::message::{Format, OwMessage, Pressure, Temperature};
+
+/// ### OwMessageBuilder
+/// Chainable configuration of an OwMessage
+/// * start with **OwMessage::builder()**
+/// * finish with **build**
+/// * unset options keep the **owrust::new()** defaults
+#[derive(Debug, Clone)]
+pub struct OwMessageBuilder {
+    message: OwMessage,
+}
+
+impl Default for OwMessageBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OwMessageBuilder {
+    /// ### new
+    /// Builder starting from the default OwMessage
+    pub fn new() -> Self {
+        OwMessageBuilder {
+            message: OwMessage::new(),
+        }
+    }
+
+    /// ### server
+    /// owserver address (e.g. localhost:4304)
+    pub fn server(mut self, target: &str) -> Self {
+        self.message.stream.set_target(target);
+        self
+    }
+
+    /// ### temperature
+    /// Temperature scale for readings
+    pub fn temperature(mut self, temperature: Temperature) -> Self {
+        self.message.temperature = temperature;
+        self
+    }
+
+    /// ### pressure
+    /// Pressure scale for readings
+    pub fn pressure(mut self, pressure: Pressure) -> Self {
+        self.message.pressure = pressure;
+        self
+    }
+
+    /// ### format
+    /// Device address format displayed
+    pub fn format(mut self, format: Format) -> Self {
+        self.message.format = format;
+        self
+    }
+
+    /// ### persist
+    /// Keep the connection to owserver open between queries
+    pub fn persist(mut self, persist: bool) -> Self {
+        self.message.stream.set_persistence(persist);
+        self
+    }
+
+    /// ### pool
+    /// Share open connections between clones of the OwMessage
+    /// * e.g. worker threads each with a clone
+    /// * turns on persistence
+    pub fn pool(mut self, pool: bool) -> Self {
+        self.message.stream.set_pool(pool);
+        self
+    }
+
+    /// ### hex
+    /// Show (and accept) values as hexidecimal bytes
+    pub fn hex(mut self, hex: bool) -> Self {
+        self.message.hex = hex;
+        self
+    }
+
+    /// ### size
+    /// Bytes requested by each read (0 for the owserver default)
+    pub fn size(mut self, size: u32) -> Self {
+        self.message.size = size;
+        self
+    }
+
+    /// ### offset
+    /// Start position (in bytes) of reads and writes
+    pub fn offset(mut self, offset: u32) -> Self {
+        self.message.offset = offset;
+        self
+    }
+
+    /// ### trim
+    /// Remove the space padding owserver puts around values
+    pub fn trim(mut self, trim: bool) -> Self {
+        self.message.trim = trim;
+        self
+    }
+
+    /// ### timeout
+    /// How long to wait for an owserver response
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.message.stream.set_read_timeout(timeout);
+        self
+    }
+
+    /// ### max_packets
+    /// Limit on continuation packets for one directory listing
+    pub fn max_packets(mut self, max_packets: usize) -> Self {
+        self.message.set_max_packets(max_packets);
+        self
+    }
+
+    /// ### build
+    /// Create the OwMessage with owserver flags computed
+    pub fn build(mut self) -> OwMessage {
+        self.message.make_flags();
+        self.message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_build() {
+        let owserver = OwMessage::builder().build();
+        assert_eq!(owserver.flags, crate::new().flags);
+    }
+
+    #[test]
+    fn flags_build() {
+        let owserver = OwMessage::builder()
+            .server("localhost:14304")
+            .temperature(Temperature::KELVIN)
+            .pressure(Pressure::PSI)
+            .format(Format::FdIdC)
+            .persist(true)
+            .hex(true)
+            .timeout(Duration::from_secs(1))
+            .build();
+        assert_eq!(
+            owserver.flags,
+            OwMessage::TEMPERATURE_K
+                | OwMessage::PRESSURE_PSI
+                | OwMessage::FORMAT_F_I_C
+                | OwMessage::PERSISTENCE
+                | OwMessage::BUS_RET
+        );
+        assert!(owserver.hex);
+    }
+
+    #[test]
+    fn pool_build() {
+        let owserver = OwMessage::builder().pool(true).build();
+        assert_eq!(
+            owserver.flags & OwMessage::PERSISTENCE,
+            OwMessage::PERSISTENCE
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! **owrust** Rust library interfaces with owserver to use 1-wire devices
+//!
+//! This is a tool in the 1-wire file system **OWFS**
+//!
+//! This library is the central part of **owrust** -- the _rust language_ OWFS programs
+//! * **OWFS** [documentation](https://owfs.org) and [code](https://github.com/owfs/owfs)
+//! * **owrust** [repository](https://github.com/alfille/owrust)
+//!
+//! ## PURPOSE
+//! TtlCache keeps recent owserver answers by path
+//! * directory listings (_--dir-cache-ttl_) and read values (_--cache-ttl_)
+//! * entries older than the ttl are not used
+//! * bounded size -- the oldest entry is dropped when full
+
+// owrust project
+// https://github.com/alfille/owrust
+//
+// This is a Rust version of my C owfs code for talking to 1-wire devices via owserver
+// Basically owserver can talk to the physical devices, and provides network access via my "owserver protocol"
+//
+// MIT Licence
+// {c} 2025 Paul H Alfille
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone)]
+pub(super) struct TtlCache<K, V> {
+    ttl: Option<Duration>,
+    entries: HashMap<K, (Instant, V)>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> TtlCache<K, V> {
+    // Most entries kept
+    const SIZE: usize = 256;
+
+    // off (no ttl) until set
+    pub(super) fn new() -> Self {
+        TtlCache {
+            ttl: None,
+            entries: HashMap::new(),
+        }
+    }
+
+    // None turns caching off
+    // * old entries are dropped either way
+    pub(super) fn set_ttl(&mut self, ttl: Option<Duration>) {
+        self.ttl = ttl;
+        self.entries.clear();
+    }
+
+    #[cfg(test)]
+    pub(super) fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    // cached value, if still fresh
+    pub(super) fn get(&mut self, key: &K) -> Option<V> {
+        let ttl = self.ttl?;
+        match self.entries.get(key) {
+            Some((when, value)) if when.elapsed() < ttl => Some(value.clone()),
+            Some(_) => {
+                self.entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    // remember a value, dropping the oldest one when full
+    pub(super) fn put(&mut self, key: K, value: V) {
+        if self.ttl.is_none() {
+            return;
+        }
+        if self.entries.len() >= Self::SIZE && !self.entries.contains_key(&key) {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (when, _))| *when)
+                .map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, (Instant::now(), value));
+    }
+
+    // drop every entry whose key matches
+    pub(super) fn remove(&mut self, matches: impl Fn(&K) -> bool) {
+        self.entries.retain(|key, _| !matches(key));
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size_test() {
+        let mut cache: TtlCache<String, u32> = TtlCache::new();
+        // off
+        cache.put("/".to_string(), 0);
+        assert_eq!(cache.get(&"/".to_string()), None);
+
+        cache.set_ttl(Some(Duration::from_secs(60)));
+        for i in 0..TtlCache::<String, u32>::SIZE as u32 + 1 {
+            cache.put(format!("/{}", i), i);
+        }
+        assert_eq!(cache.entries.len(), TtlCache::<String, u32>::SIZE);
+        // first one in was dropped
+        assert_eq!(cache.get(&"/0".to_string()), None);
+        assert_eq!(cache.get(&"/1".to_string()), Some(1));
+
+        cache.remove(|k| k.starts_with("/1"));
+        assert_eq!(cache.get(&"/1".to_string()), None);
+        assert_eq!(cache.get(&"/2".to_string()), Some(2));
+    }
+}
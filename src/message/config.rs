@@ -0,0 +1,201 @@
+//! **owrust** Rust library interfaces with owserver to use 1-wire devices
+//!
+//! This is a tool in the 1-wire file system **OWFS**
+//!
+//! This library is the central part of **owrust** -- the _rust language_ OWFS programs
+//! * **OWFS** [documentation](https://owfs.org) and [code](https://github.com/owfs/owfs)
+//! * **owrust** [repository](https://github.com/alfille/owrust)
+//!
+//! ## PURPOSE
+//! OwConfig holds the OwMessage settings as plain data
+//! * fill in from a file, database or other program
+//! * apply with **OwMessage::from_config**, get back with **OwMessage::to_config**
+//! * separate from command line parsing
+//! * the `serde` feature adds Serialize and Deserialize (e.g. to save as JSON)
+//!
+//! ## EXAMPLES
+//! ```
+//! use owrust::message::{OwMessage, Temperature};
+//! use owrust::message::config::OwConfig;
+//!
+//! let config = OwConfig {
+//!     server: "192.168.1.20:4304".to_string(),
+//!     temperature: Temperature::FARENHEIT,
+//!     ..Default::default()
+//! };
+//! let owserver = OwMessage::from_config(config);
+//! ```
+
+// owrust project
+// https://github.com/alfille/owrust
+//
+// This is a Rust version of my C owfs code for talking to 1-wire devices via owserver
+// Basically owserver can talk to the physical devices, and provides network access via my "owserver protocol"
+//
+// MIT Licence
+// {c} 2025 Paul H Alfille
+
+use std::time::Duration;
+
+use crate::message::{Format, OwMessage, Pressure, Temperature};
+
+/// ### OwConfig
+/// Settings for an OwMessage
+/// * server -- owserver address (comma-separated list for failover)
+/// * temperature, pressure -- scales for readings
+/// * format -- device address format
+/// * persist -- keep the owserver connection open
+/// * timeout -- how long to wait for an owserver response
+/// * hex -- values shown (and written) as hexidecimal bytes
+/// * size, offset -- bytes requested by reads (0 for owserver default) and where they start
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct OwConfig {
+    pub server: String,
+    pub temperature: Temperature,
+    pub pressure: Pressure,
+    pub format: Format,
+    pub persist: bool,
+    pub timeout: Duration,
+    pub hex: bool,
+    pub size: u32,
+    pub offset: u32,
+}
+
+/// Default OwConfig
+/// Same settings as **owrust::new()**
+impl Default for OwConfig {
+    fn default() -> Self {
+        OwConfig {
+            server: "localhost:4304".to_string(),
+            temperature: Temperature::DEFAULT,
+            pressure: Pressure::DEFAULT,
+            format: Format::DEFAULT,
+            persist: false,
+            timeout: Duration::from_secs(5),
+            hex: false,
+            size: 0,
+            offset: 0,
+        }
+    }
+}
+
+impl OwMessage {
+    /// ### from_config
+    /// Create an OwMessage from an OwConfig
+    /// * owserver flags are computed
+    pub fn from_config(config: OwConfig) -> OwMessage {
+        OwMessage::builder()
+            .server(&config.server)
+            .temperature(config.temperature)
+            .pressure(config.pressure)
+            .format(config.format)
+            .persist(config.persist)
+            .timeout(config.timeout)
+            .hex(config.hex)
+            .size(config.size)
+            .offset(config.offset)
+            .build()
+    }
+
+    /// ### to_config
+    /// Current settings as an OwConfig
+    /// * **from_config** gives back an equivalent OwMessage
+    pub fn to_config(&self) -> OwConfig {
+        OwConfig {
+            server: self.stream.targets(),
+            temperature: self.temperature.clone(),
+            pressure: self.pressure.clone(),
+            format: self.format.clone(),
+            persist: self.stream.get_persistence(),
+            timeout: self.stream.read_timeout(),
+            hex: self.hex,
+            size: self.size,
+            offset: self.offset,
+        }
+    }
+}
+
+/// ### OwMessage from an OwConfig
+/// * same as **OwMessage::from_config**
+impl From<OwConfig> for OwMessage {
+    fn from(config: OwConfig) -> Self {
+        OwMessage::from_config(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config() {
+        let owserver = OwMessage::from_config(OwConfig::default());
+        assert_eq!(owserver.flags, crate::new().flags);
+    }
+
+    #[test]
+    fn flags_config() {
+        let config = OwConfig {
+            server: "localhost:14304".to_string(),
+            temperature: Temperature::RANKINE,
+            pressure: Pressure::INHG,
+            format: Format::FIC,
+            persist: true,
+            timeout: Duration::from_secs(1),
+            ..Default::default()
+        };
+        let owserver = OwMessage::from_config(config);
+        assert_eq!(
+            owserver.flags,
+            OwMessage::TEMPERATURE_R
+                | OwMessage::PRESSURE_INHG
+                | OwMessage::FORMAT_FIC
+                | OwMessage::PERSISTENCE
+                | OwMessage::BUS_RET
+        );
+        assert_eq!(owserver.stream.target(), "localhost:14304");
+        assert!(owserver.stream.get_persistence());
+    }
+
+    fn sample() -> OwConfig {
+        OwConfig {
+            server: "host1:4304,host2:4304".to_string(),
+            temperature: Temperature::KELVIN,
+            pressure: Pressure::PSI,
+            format: Format::FdIdC,
+            persist: true,
+            timeout: Duration::from_millis(2500),
+            hex: true,
+            size: 8,
+            offset: 32,
+        }
+    }
+
+    #[test]
+    fn to_config_test() {
+        assert_eq!(crate::new().to_config(), OwConfig::default());
+        let owserver = OwMessage::from_config(sample());
+        assert_eq!(owserver.to_config(), sample());
+        let owserver: OwMessage = sample().into();
+        assert_eq!(owserver.to_config(), sample());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_config_test() {
+        let json = serde_json::to_string(&sample()).unwrap();
+        assert!(json.contains("\"KELVIN\""));
+        let back: OwConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, sample());
+
+        // missing fields take the defaults
+        let partial: OwConfig = serde_json::from_str(r#"{"server":"host:4304"}"#).unwrap();
+        assert_eq!(partial.server, "host:4304");
+        assert_eq!(partial.timeout, OwConfig::default().timeout);
+    }
+}
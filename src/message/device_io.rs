@@ -0,0 +1,200 @@
+//! **owrust** Rust library interfaces with owserver to use 1-wire devices
+//!
+//! This is a tool in the 1-wire file system **OWFS**
+//!
+//! This library is the central part of **owrust** -- the _rust language_ OWFS programs
+//! * **OWFS** [documentation](https://owfs.org) and [code](https://github.com/owfs/owfs)
+//! * **owrust** [repository](https://github.com/alfille/owrust)
+//!
+//! ## PURPOSE
+//! DeviceReader and DeviceWriter treat a 1-wire memory file like an ordinary file
+//! * `std::io::Read` and `std::io::Write`
+//! * each keeps its own position, paging through the device with offset and size
+//! * created by **OwMessage::open_reader** and **OwMessage::open_writer**
+//!
+//! ## EXAMPLES
+//! ```no_run
+//! let mut owserver = owrust::new();
+//! let mut memory = owserver.open_reader("/23.5F2F2F2F2F2F/memory");
+//! let mut file = std::fs::File::create("memory.bin").unwrap();
+//! std::io::copy(&mut memory, &mut file).unwrap();
+//! ```
+
+// owrust project
+// https://github.com/alfille/owrust
+//
+// This is a Rust version of my C owfs code for talking to 1-wire devices via owserver
+// Basically owserver can talk to the physical devices, and provides network access via my "owserver protocol"
+//
+// MIT Licence
+// {c} 2025 Paul H Alfille
+
+use std::io;
+
+use crate::OwMessage;
+
+// Largest single owserver read
+const MAX_CHUNK: usize = 65536;
+
+/// ### DeviceReader
+/// `std::io::Read` over a 1-wire memory file
+/// * reads continue from where the last one ended
+/// * an empty owserver reply is the end of the memory
+pub struct DeviceReader<'a> {
+    owserver: &'a mut OwMessage,
+    path: String,
+    offset: u32,
+    done: bool,
+}
+
+impl<'a> DeviceReader<'a> {
+    pub(super) fn new(owserver: &'a mut OwMessage, path: String) -> Self {
+        DeviceReader {
+            owserver,
+            path,
+            offset: 0,
+            done: false,
+        }
+    }
+
+    /// ### position
+    /// Offset (in bytes) of the next read
+    pub fn position(&self) -> u32 {
+        self.offset
+    }
+}
+
+impl io::Read for DeviceReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.done || buf.is_empty() {
+            return Ok(0);
+        }
+        let size = buf.len().min(MAX_CHUNK) as u32;
+        let data = self.owserver.read_range(&self.path, self.offset, size)?;
+        if data.is_empty() {
+            self.done = true;
+            return Ok(0);
+        }
+        // never trust owserver to honor the size
+        let n = data.len().min(buf.len());
+        buf[..n].copy_from_slice(&data[..n]);
+        self.offset += n as u32;
+        Ok(n)
+    }
+}
+
+/// ### DeviceWriter
+/// `std::io::Write` over a 1-wire memory file
+/// * writes continue from where the last one ended
+/// * each write goes straight to owserver (nothing to flush)
+pub struct DeviceWriter<'a> {
+    owserver: &'a mut OwMessage,
+    path: String,
+    offset: u32,
+}
+
+impl<'a> DeviceWriter<'a> {
+    pub(super) fn new(owserver: &'a mut OwMessage, path: String) -> Self {
+        DeviceWriter {
+            owserver,
+            path,
+            offset: 0,
+        }
+    }
+
+    /// ### position
+    /// Offset (in bytes) of the next write
+    pub fn position(&self) -> u32 {
+        self.offset
+    }
+}
+
+impl io::Write for DeviceWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let n = buf.len().min(MAX_CHUNK);
+        self.owserver.write_at(&self.path, self.offset, &buf[..n])?;
+        self.offset += n as u32;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::query::OwQuery;
+    use crate::message::tests::{mock_client, mock_owserver, reply};
+    use std::io::{Read, Write};
+    use std::sync::{Arc, Mutex};
+
+    fn memory() -> Vec<u8> {
+        (0..200u32).map(|i| i as u8).collect()
+    }
+
+    #[test]
+    fn reader_test() {
+        let address = mock_owserver(|q| match q.mtype {
+            OwQuery::READ => {
+                let memory = memory();
+                let start = (q.offset as usize).min(memory.len());
+                let end = (start + q.size as usize).min(memory.len());
+                vec![reply(0, &memory[start..end])]
+            }
+            _ => vec![],
+        });
+        let mut owc = mock_client(&address);
+        let mut reader = owc.open_reader("/23.5F2F2F2F2F2F/memory");
+
+        // small read first, then the rest
+        let mut head = [0u8; 10];
+        reader.read_exact(&mut head).unwrap();
+        assert_eq!(head.to_vec(), memory()[..10].to_vec());
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, memory()[10..].to_vec());
+        assert_eq!(reader.position(), 200);
+    }
+
+    #[test]
+    fn reader_error_test() {
+        // owserver error part way through is not the end of the memory
+        let address = mock_owserver(|q| match q.offset as usize {
+            start if start < 100 => {
+                let end = (start + q.size as usize).min(100);
+                vec![reply(0, &memory()[start..end])]
+            }
+            _ => vec![reply(-5, b"")],
+        });
+        let mut owc = mock_client(&address);
+        let mut reader = owc.open_reader("/23.5F2F2F2F2F2F/memory");
+        let mut all = Vec::new();
+        assert!(reader.read_to_end(&mut all).is_err());
+        assert_eq!(all, memory()[..100].to_vec());
+    }
+
+    #[test]
+    fn writer_test() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let w = written.clone();
+        let address = mock_owserver(move |q| {
+            let value = &q.content[q.content.len() - q.size as usize..];
+            w.lock().unwrap().push((q.offset, value.to_vec()));
+            vec![reply(0, b"")]
+        });
+        let mut owc = mock_client(&address);
+        let mut writer = owc.open_writer("/23.5F2F2F2F2F2F/memory");
+        writer.write_all(b"HELLO").unwrap();
+        writer.write_all(b"WORLD").unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.position(), 10);
+        assert_eq!(
+            *written.lock().unwrap(),
+            vec![(0, b"HELLO".to_vec()), (5, b"WORLD".to_vec())]
+        );
+    }
+}
@@ -0,0 +1,98 @@
+//! **owrust** Rust library interfaces with owserver to use 1-wire devices
+//!
+//! This is a tool in the 1-wire file system **OWFS**
+//!
+//! This library is the central part of **owrust** -- the _rust language_ OWFS programs
+//! * **OWFS** [documentation](https://owfs.org) and [code](https://github.com/owfs/owfs)
+//! * **owrust** [repository](https://github.com/alfille/owrust)
+//!
+//! ## PURPOSE
+//! Find owservers that advertise themselves with zeroconf (mDNS)
+//! * owserver announces the `_owserver._tcp` service type
+//! * only built with the `mdns` feature
+
+// owrust project
+// https://github.com/alfille/owrust
+//
+// This is a Rust version of my C owfs code for talking to 1-wire devices via owserver
+// Basically owserver can talk to the physical devices, and provides network access via my "owserver protocol"
+//
+// MIT Licence
+// {c} 2025 Paul H Alfille
+
+use mdns_sd::{ServiceDaemon, ServiceEvent};
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use crate::error::{OwEResult, OwError};
+
+/// zeroconf service type announced by owserver
+pub const SERVICE: &str = "_owserver._tcp.local.";
+
+/// ### browse
+/// Collect `host:port` targets for a service type
+/// * listens for `wait` before returning
+/// * each address of a resolved service is a separate target
+/// * targets are sorted with duplicates removed
+pub fn browse(service: &str, wait: Duration) -> OwEResult<Vec<String>> {
+    let daemon = ServiceDaemon::new().map_err(mdns_error)?;
+    let receiver = daemon.browse(service).map_err(mdns_error)?;
+
+    let mut targets: Vec<String> = Vec::new();
+    let end = Instant::now() + wait;
+    while let Some(left) = end.checked_duration_since(Instant::now()) {
+        match receiver.recv_timeout(left) {
+            Ok(ServiceEvent::ServiceResolved(info)) => {
+                for ip in info.get_addresses() {
+                    targets.push(target(*ip, info.get_port()));
+                }
+            }
+            Ok(_) => (),
+            Err(_) => break,
+        }
+    }
+    let _ = daemon.shutdown();
+
+    targets.sort();
+    targets.dedup();
+    Ok(targets)
+}
+
+// owserver target string (the -s option) for an address
+// IPv6 addresses are bracketed
+pub(crate) fn target(ip: IpAddr, port: u16) -> String {
+    SocketAddr::new(ip, port).to_string()
+}
+
+fn mdns_error(e: mdns_sd::Error) -> OwError {
+    OwError::General(format!("mDNS discovery: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_args::{OwLib, Parser};
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn target_test() {
+        assert_eq!(
+            target(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 20)), 4304),
+            "192.168.1.20:4304"
+        );
+        assert_eq!(target(IpAddr::V6(Ipv6Addr::LOCALHOST), 4304), "[::1]:4304");
+    }
+
+    #[test]
+    fn parser_accepts_target() {
+        for ip in [
+            IpAddr::V4(Ipv4Addr::new(192, 168, 1, 20)),
+            IpAddr::V6(Ipv6Addr::LOCALHOST),
+        ] {
+            let s = target(ip, 4304);
+            let mut owserver = crate::new();
+            let paths = OwLib.vector_line(&mut owserver, vec!["-s", &s]).unwrap();
+            assert!(paths.is_empty(), "{} left as a path", s);
+        }
+    }
+}
@@ -11,7 +11,6 @@ use crate::console::console_lines;
 use crate::error::{OwEResult, OwError};
 use pico_args::Arguments;
 use std::ffi::OsString;
-use std::process;
 
 /// ### OwDir
 /// Structure encapsulating the command line argument processing and help for **owdir**
@@ -36,8 +35,11 @@ impl Parser for OwDir {
         );
         self.server_options(owserver, args)?;
         self.directory_options(owserver, args)?;
+        self.separator_options(owserver, args)?;
+        self.columns_options(owserver, args)?;
         self.format_options(owserver, args)?;
         self.persist_options(owserver, args)?;
+        self.pace_options(owserver, args)?;
         Ok(())
     }
 }
@@ -66,7 +68,10 @@ impl Parser for OwTree {
         self.server_options(owserver, args)?;
         self.directory_options(owserver, args)?;
         self.format_options(owserver, args)?;
+        self.color_options(owserver, args)?;
+        self.style_options(owserver, args)?;
         self.persist_options(owserver, args)?;
+        self.pace_options(owserver, args)?;
         // special consideration for owtree -- alway persistent
         owserver.stream.set_persistence(true);
         Ok(())
@@ -97,11 +102,14 @@ impl Parser for OwGet {
         );
         self.server_options(owserver, args)?;
         self.directory_options(owserver, args)?;
+        self.separator_options(owserver, args)?;
         self.format_options(owserver, args)?;
         self.temperature_options(owserver, args)?;
         self.pressure_options(owserver, args)?;
         self.data_options(owserver, args)?;
+        self.timestamp_options(owserver, args)?;
         self.persist_options(owserver, args)?;
+        self.pace_options(owserver, args)?;
         Ok(())
     }
 }
@@ -132,7 +140,11 @@ impl Parser for OwRead {
         self.temperature_options(owserver, args)?;
         self.pressure_options(owserver, args)?;
         self.data_options(owserver, args)?;
+        self.csv_options(owserver, args)?;
+        self.timestamp_options(owserver, args)?;
+        self.interval_options(owserver, args)?;
         self.persist_options(owserver, args)?;
+        self.pace_options(owserver, args)?;
         Ok(())
     }
 }
@@ -163,7 +175,9 @@ impl Parser for OwWrite {
         self.temperature_options(owserver, args)?;
         self.pressure_options(owserver, args)?;
         self.data_options(owserver, args)?;
+        self.dry_run_options(owserver, args)?;
         self.persist_options(owserver, args)?;
+        self.pace_options(owserver, args)?;
         Ok(())
     }
 }
@@ -190,7 +204,9 @@ impl Parser for OwSize {
             ],
         );
         self.server_options(owserver, args)?;
+        self.size_options(owserver, args)?;
         self.persist_options(owserver, args)?;
+        self.pace_options(owserver, args)?;
         Ok(())
     }
 }
@@ -219,6 +235,58 @@ impl Parser for OwPresent {
         );
         self.server_options(owserver, args)?;
         self.persist_options(owserver, args)?;
+        self.pace_options(owserver, args)?;
+        Ok(())
+    }
+}
+
+/// ### OwProbe
+/// Structure encapsulating the command line argument processing and help for **owprobe**
+///
+/// Uses default implementation except function **help_and_options**
+pub struct OwProbe;
+impl Parser for OwProbe {
+    fn help_and_options(
+        &self,
+        owserver: &mut crate::OwMessage,
+        args: &mut Arguments,
+    ) -> OwEResult<()> {
+        let _ = self.helper(
+            args,
+            &[
+                "owprobe [OPTIONS]",
+                "\tList the 1-wire buses and their adapters",
+                "",
+                "OPTIONS",
+            ],
+        );
+        self.server_options(owserver, args)?;
+        Ok(())
+    }
+}
+
+/// ### OwFormat
+/// Structure encapsulating the command line argument processing and help for **owformat**
+///
+/// Uses default implementation except function **help_and_options**
+pub struct OwFormat;
+impl Parser for OwFormat {
+    fn help_and_options(
+        &self,
+        owserver: &mut crate::OwMessage,
+        args: &mut Arguments,
+    ) -> OwEResult<()> {
+        let _ = self.helper(
+            args,
+            &[
+                "owformat [OPTIONS] ADDRESS",
+                "\tShow a 1-wire device address in another format",
+                "\tMore than one ADDRESS can be given",
+                "",
+                "OPTIONS",
+            ],
+        );
+        self.format_options(owserver, args)?;
         Ok(())
     }
 }
@@ -277,7 +345,9 @@ impl Parser for OwLib {
         self.pressure_options(owserver, args)?;
         self.data_options(owserver, args)?;
         self.directory_options(owserver, args)?;
+        self.size_options(owserver, args)?;
         self.persist_options(owserver, args)?;
+        self.pace_options(owserver, args)?;
         Ok(())
     }
 }
@@ -360,8 +430,55 @@ pub trait Parser {
         // debug
         while args.contains(["-d", "--debug"]) {
             owserver.debug += 1;
-            eprintln!("Debuging level {}", owserver.debug);
         }
+        if owserver.debug > 0 {
+            crate::logging::debug_level(owserver.debug);
+            log::debug!("Debugging level {}", owserver.debug);
+        }
+
+        // profile
+        if args.contains("--profile") {
+            owserver.profile = true;
+        }
+
+        // output to file
+        let output: Option<String> = args.opt_value_from_str("--output")?;
+        if let Some(file) = output {
+            crate::console::console_to_file(&file)?;
+        }
+
+        // fresh data for every query
+        if args.contains("--via-uncached") {
+            owserver.uncached = true;
+        }
+
+        // no per-path error messages
+        if args.contains(["-q", "--quiet"]) {
+            owserver.quiet = true;
+        }
+
+        // device address case
+        let upcase = args.contains("--upcase");
+        if args.contains("--downcase") {
+            if upcase {
+                eprintln!("--upcase and --downcase are mutually exclusive. Using --upcase");
+            } else {
+                owserver.address_case = super::AddressCase::LOWER;
+            }
+        }
+        if upcase {
+            owserver.address_case = super::AddressCase::UPPER;
+        }
+
+        // version (before paths are gathered, so works alone)
+        if args.contains(["-V", "--version"]) {
+            console_lines(version_lines());
+            program_exit();
+            return Ok(Vec::new());
+        }
+
+        // show owserver flags (after the rest of the configuration)
+        let show_flags = args.contains("--show-flags");
 
         // Handle the help flag for the trailing message
         if args.contains(["-h", "--help"]) {
@@ -369,29 +486,47 @@ pub trait Parser {
                 "",
                 "General",
                 "\t-h\t--help\tThis help message",
+                "\t-V\t--version\tShow program and protocol version",
                 "\t-d\t--debug\tShow debugging information",
+                "\t-q\t--quiet\tDon't show problems with individual paths",
+                "\t--profile\tShow connect/send/receive timing for each query",
+                "\t--via-uncached\tRead every path through /uncached for fresh data",
+                "\t--upcase\tSend device addresses in upper case",
+                "\t--downcase\tSend device addresses in lower case",
+                "\t--output FILE\tWrite results to FILE instead of the screen",
+                "\t--show-flags\tShow the owserver flags these options produce",
                 "",
                 "See https://github.com/alfille/owrust for more information",
             ]);
-            process::exit(0);
+            program_exit();
+            return Ok(Vec::new());
         }
 
         // Gather PATH (and VALUES if owwrite) for return
         let mut result: Vec<String> = Vec::new();
         for os in args.clone().finish() {
             match os.into_string() {
+                // leftover flag is a typo or an option this program doesn't take
+                // * negative numbers are still allowed as values (owwrite)
+                Ok(s) if s.starts_with('-') && s.parse::<f64>().is_err() => {
+                    return Err(OwError::Input(format!("unknown option {}", s)));
+                }
                 Ok(s) => result.push(s),
                 Err(_e) => {
-                    return Err(OwError::Input("Bad command line entry.".into()));
+                    return Err(OwError::input("Bad command line entry."));
                 }
             }
         }
-        if owserver.debug > 1 {
-            eprintln!("{} path entries", result.len());
-        }
+        log::trace!("{} path entries", result.len());
 
         // owserver use configuration information to set up message parameters
         owserver.make_flags();
+
+        if show_flags {
+            console_lines(flag_lines(owserver.flags));
+            program_exit();
+            return Ok(Vec::new());
+        }
         Ok(result)
     }
 
@@ -420,19 +555,31 @@ pub trait Parser {
                 "\t-F\t--fahrenheit",
                 "\t-K\t--kelvin",
                 "\t-R\t--rankine",
+                "\t--scale NAME\tcelsius, fahrenheit, kelvin or rankine",
             ],
         ) {
+            scale_option(owserver, args)?;
             // Temperature
-            if args.contains(["-C", "--Celsius"]) {
+            // documented lower case and original capitalized spellings both accepted
+            if contains_any(args, &["-C", "--celsius", "--Celsius"]) {
                 owserver.temperature = super::Temperature::CELSIUS;
             }
-            if args.contains(["-F", "--Farenheit"]) {
+            if contains_any(
+                args,
+                &[
+                    "-F",
+                    "--fahrenheit",
+                    "--Fahrenheit",
+                    "--farenheit",
+                    "--Farenheit",
+                ],
+            ) {
                 owserver.temperature = super::Temperature::FARENHEIT;
             }
-            if args.contains(["-K", "--Kelvin"]) {
+            if contains_any(args, &["-K", "--kelvin", "--Kelvin"]) {
                 owserver.temperature = super::Temperature::KELVIN;
             }
-            if args.contains(["-R", "--Rankine"]) {
+            if contains_any(args, &["-R", "--rankine", "--Rankine"]) {
                 owserver.temperature = super::Temperature::RANKINE;
             }
         }
@@ -455,8 +602,10 @@ pub trait Parser {
                 "\t-atm   atmospheres",
                 "\t-ps    Pascals",
                 "\t-psi   pounds / in^2",
+                "\t--scale NAME  mmhg, inhg, mbar, atm, pa or psi",
             ],
         ) {
+            scale_option(owserver, args)?;
             // Pressure
             if args.contains("--mmhg") {
                 owserver.pressure = super::Pressure::MMHG;
@@ -507,21 +656,50 @@ pub trait Parser {
             &[
                 "Data display (default text",
                 "\t--hex\tShow hexidecimal bytes",
-                "\t--size\tLimit data size returned (in bytes)",
+                "\t--raw\tWrite unchanged bytes (no newline)",
+                "\t--strict\tError on non-text values (instead of hex) and short reads",
+                "\t--trim\tRemove the space padding around values",
+                "\t--units\tShow the unit after temperatures and pressures (e.g. 85.75 °F)",
+                "\t--size\tLimit data size returned (in bytes, 0 for owserver default)",
                 "\t--offset\tposition (in bytes) to start data returned",
+                "\t\tdecimal or hex with 0x (e.g. 32 or 0x20)",
+                "\t--cache-ttl SECONDS\tReuse values read this recently",
             ],
         ) {
             // Display
             if args.contains("--hex") {
                 owserver.hex = true;
             }
-            let y = args.opt_value_from_str("--size")?;
+            if args.contains("--raw") {
+                if owserver.hex {
+                    eprintln!("--raw and --hex are mutually exclusive. Using --hex");
+                } else {
+                    owserver.raw = true;
+                }
+            }
+            if args.contains("--strict") {
+                owserver.strict = true;
+            }
+            if args.contains("--trim") {
+                owserver.trim = true;
+            }
+            if args.contains("--units") {
+                owserver.units = true;
+            }
+            let y: Option<String> = args.opt_value_from_str("--size")?;
             if let Some(x) = y {
-                owserver.size = x;
+                owserver.size = number_match(&x)?;
             }
-            let y = args.opt_value_from_str("--offset")?;
+            let y: Option<String> = args.opt_value_from_str("--offset")?;
             if let Some(x) = y {
-                owserver.offset = x;
+                owserver.offset = number_match(&x)?;
+            }
+            let ttl: Option<f64> = args.opt_value_from_str("--cache-ttl")?;
+            if let Some(x) = ttl {
+                if !x.is_finite() || x < 0.0 {
+                    return Err(OwError::Input(format!("Bad cache time {}", x)));
+                }
+                owserver.set_cache_ttl(Some(std::time::Duration::from_secs_f64(x)));
             }
         }
         Ok(())
@@ -537,6 +715,9 @@ pub trait Parser {
             &[
                 "OwServer address (default localhost:4304)",
                 "\t-s\t--server\tIp address of owserver to contact",
+                "\t\tcomma-separated list for failover (e.g. host1:4304,host2:4304)",
+                "\t--protocol-version\tProtocol version sent to owserver (default 0)",
+                "\t--timeout-connect SECONDS\tWait for owserver to accept a connection (default 5)",
             ],
         ) {
             // Server
@@ -544,6 +725,19 @@ pub trait Parser {
             if let Some(s) = serv {
                 owserver.stream.set_target(&s);
             }
+            let version: Option<u32> = args.opt_value_from_str("--protocol-version")?;
+            if let Some(v) = version {
+                owserver.set_protocol_version(v)?;
+            }
+            let connect: Option<f64> = args.opt_value_from_str("--timeout-connect")?;
+            if let Some(x) = connect {
+                if !x.is_finite() || x <= 0.0 {
+                    return Err(OwError::Input(format!("Bad connect timeout {}", x)));
+                }
+                owserver
+                    .stream
+                    .set_connect_timeout(std::time::Duration::from_secs_f64(x));
+            }
         }
         Ok(())
     }
@@ -558,6 +752,8 @@ pub trait Parser {
             &[
                 "Listening address (no default but required)",
                 "\t-p\t--port\tIp address this program will answer on",
+                "\t--workers N\tConnections handled at once (default: number of CPUs)",
+                "\t--heartbeat SECONDS\tShow a status line on stderr this often",
             ],
         ) {
             // Listener
@@ -565,6 +761,17 @@ pub trait Parser {
             if listener.is_some() {
                 owserver.listener = listener;
             }
+            let workers: Option<usize> = args.opt_value_from_str("--workers")?;
+            if let Some(w) = workers {
+                owserver.workers = w;
+            }
+            let heartbeat: Option<f64> = args.opt_value_from_str("--heartbeat")?;
+            if let Some(x) = heartbeat {
+                if !x.is_finite() || x <= 0.0 {
+                    return Err(OwError::Input(format!("Bad heartbeat interval {}", x)));
+                }
+                owserver.heartbeat = Some(std::time::Duration::from_secs_f64(x));
+            }
         }
         Ok(())
     }
@@ -580,7 +787,13 @@ pub trait Parser {
                 "Directory display options",
                 "\t--dir\tMark directories with a trailing '/'",
                 "\t--bare\tExclude non-device entries",
+                "\t--no-bus\tAsk owserver not to list bus entries (BUS_RET flag off)",
+                "\t--dir-cache-ttl SECONDS\tReuse directory listings this recent",
                 "\t--prune\tExclude some convenience device entries (e.g. address)",
+                "\t--first-only\tOnly the first entry (e.g. to check for any device)",
+                "\t--include PATTERN\tOnly entries whose name matches (* and ? wildcards)",
+                "\t--exclude PATTERN\tOmit entries whose name matches (* and ? wildcards)",
+                "\t\tmay be given more than once",
             ],
         ) {
             // Slash
@@ -590,10 +803,223 @@ pub trait Parser {
             if args.contains("--bare") {
                 owserver.bare = true;
             }
+            if args.contains("--no-bus") {
+                owserver.no_bus = true;
+            }
             if args.contains("--prune") {
                 owserver.bare = true;
                 owserver.prune = true;
             }
+            if args.contains("--first-only") {
+                owserver.first_only = true;
+            }
+            let ttl: Option<f64> = args.opt_value_from_str("--dir-cache-ttl")?;
+            if let Some(x) = ttl {
+                if !x.is_finite() || x < 0.0 {
+                    return Err(OwError::Input(format!("Bad directory cache time {}", x)));
+                }
+                owserver.set_dir_cache_ttl(Some(std::time::Duration::from_secs_f64(x)));
+            }
+            owserver.include = args.values_from_str("--include")?;
+            owserver.exclude = args.values_from_str("--exclude")?;
+        }
+        Ok(())
+    }
+
+    fn size_options(&self, owserver: &mut crate::OwMessage, args: &mut Arguments) -> OwEResult<()> {
+        if !self.helper(
+            args,
+            &[
+                "Size display",
+                "\t--human\tShow sizes with units (e.g. 12 B, 2.0 KiB)",
+            ],
+        ) && args.contains("--human")
+        {
+            owserver.human = true;
+        }
+        Ok(())
+    }
+
+    fn dry_run_options(
+        &self,
+        owserver: &mut crate::OwMessage,
+        args: &mut Arguments,
+    ) -> OwEResult<()> {
+        if !self.helper(
+            args,
+            &[
+                "Safety",
+                "\t--dry-run\tShow what would be written, without writing",
+            ],
+        ) && args.contains("--dry-run")
+        {
+            owserver.dry_run = true;
+        }
+        Ok(())
+    }
+
+    fn color_options(
+        &self,
+        owserver: &mut crate::OwMessage,
+        args: &mut Arguments,
+    ) -> OwEResult<()> {
+        if !self.helper(
+            args,
+            &[
+                "Color display",
+                "\t--color WHEN\tauto (only on a terminal), always or never",
+            ],
+        ) {
+            let color: Option<String> = args.opt_value_from_str("--color")?;
+            if let Some(c) = color {
+                owserver.color = super::Color::try_from(c.as_str())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn style_options(
+        &self,
+        owserver: &mut crate::OwMessage,
+        args: &mut Arguments,
+    ) -> OwEResult<()> {
+        if !self.helper(
+            args,
+            &[
+                "Tree drawing",
+                "\t--style STYLE\tunicode (default), ascii or indent (no lines)",
+            ],
+        ) {
+            let style: Option<String> = args.opt_value_from_str("--style")?;
+            if let Some(s) = style {
+                owserver.tree_style = super::TreeStyle::try_from(s.as_str())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn separator_options(
+        &self,
+        owserver: &mut crate::OwMessage,
+        args: &mut Arguments,
+    ) -> OwEResult<()> {
+        if !self.helper(
+            args,
+            &[
+                "Output separator",
+                "\t--newline\tEnd each entry with a newline (default)",
+                "\t--null\tEnd each entry with a NUL byte (for xargs -0)",
+            ],
+        ) {
+            let newline = args.contains("--newline");
+            if args.contains("--null") {
+                if newline {
+                    eprintln!("--null and --newline are mutually exclusive. Using --newline");
+                } else {
+                    owserver.null = true;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn columns_options(
+        &self,
+        owserver: &mut crate::OwMessage,
+        args: &mut Arguments,
+    ) -> OwEResult<()> {
+        if !self.helper(
+            args,
+            &[
+                "Column display",
+                "\t--columns N\tArrange entries in N columns (like ls)",
+            ],
+        ) {
+            let y = args.opt_value_from_str("--columns")?;
+            if let Some(x) = y {
+                owserver.columns = x;
+            }
+        }
+        Ok(())
+    }
+
+    fn csv_options(&self, owserver: &mut crate::OwMessage, args: &mut Arguments) -> OwEResult<()> {
+        if !self.helper(
+            args,
+            &[
+                "Spreadsheet output",
+                "\t--csv\tWrite path,value rows with a header line",
+            ],
+        ) && args.contains("--csv")
+        {
+            owserver.csv = true;
+        }
+        Ok(())
+    }
+
+    fn timestamp_options(
+        &self,
+        owserver: &mut crate::OwMessage,
+        args: &mut Arguments,
+    ) -> OwEResult<()> {
+        if !self.helper(
+            args,
+            &[
+                "Timestamps for logging",
+                "\t--timestamp\tStart each reading with the time (RFC3339 UTC)",
+                "\t--epoch\tTimestamp in seconds since 1970 (implies --timestamp)",
+            ],
+        ) {
+            if args.contains("--timestamp") {
+                owserver.timestamp = true;
+            }
+            if args.contains("--epoch") {
+                owserver.timestamp = true;
+                owserver.epoch = true;
+            }
+        }
+        Ok(())
+    }
+
+    fn interval_options(
+        &self,
+        owserver: &mut crate::OwMessage,
+        args: &mut Arguments,
+    ) -> OwEResult<()> {
+        if !self.helper(
+            args,
+            &[
+                "Repeated readings",
+                "\t--interval SECONDS\tRead again every SECONDS (may be fractional)",
+                "\t--interval-align\tStart on a clock multiple of the interval",
+            ],
+        ) {
+            let y: Option<f64> = args.opt_value_from_str("--interval")?;
+            if let Some(x) = y {
+                if !x.is_finite() || x <= 0.0 {
+                    return Err(OwError::Input(format!("Bad interval {}", x)));
+                }
+                owserver.interval = Some(std::time::Duration::from_secs_f64(x));
+            }
+            if args.contains("--interval-align") {
+                owserver.align = true;
+            }
+        }
+        Ok(())
+    }
+
+    fn pace_options(&self, owserver: &mut crate::OwMessage, args: &mut Arguments) -> OwEResult<()> {
+        if !self.helper(
+            args,
+            &[
+                "Pacing for slow or fragile 1-wire buses",
+                "\t--min-interval MS\tAt least MS milliseconds between queries",
+            ],
+        ) {
+            let y: Option<u64> = args.opt_value_from_str("--min-interval")?;
+            if let Some(ms) = y {
+                owserver.min_interval = std::time::Duration::from_millis(ms);
+            }
         }
         Ok(())
     }
@@ -619,6 +1045,74 @@ pub trait Parser {
     }
 }
 
+// text for --version
+fn version_lines() -> Vec<String> {
+    vec![
+        format!("owrust version {}", env!("CARGO_PKG_VERSION")),
+        format!(
+            "owserver protocol version {}",
+            super::query::OwQuery::SENDVERSION
+        ),
+    ]
+}
+
+// text for --show-flags
+// * flag word in hex, then each decoded field
+fn flag_lines(flags: u32) -> Vec<String> {
+    let mut lines = vec![format!("Flags 0x{:08X}", flags)];
+    for (field, value) in crate::OwMessage::flag_fields(flags) {
+        let value = match value {
+            "" => "-",
+            v => v,
+        };
+        lines.push(format!("\t{}\t{}", field, value));
+    }
+    lines
+}
+
+// Normal end of program after --help or --version
+// * tests continue instead, so the branch can be checked
+fn program_exit() {
+    #[cfg(not(test))]
+    std::process::exit(0);
+}
+
+// --scale NAME for either a temperature or pressure scale
+// may be given more than once (e.g. --scale f --scale psi)
+fn scale_option(owserver: &mut crate::OwMessage, args: &mut Arguments) -> OwEResult<()> {
+    let names: Vec<String> = args.values_from_str("--scale")?;
+    for name in names {
+        if let Ok(t) = super::Temperature::try_from(name.as_str()) {
+            owserver.temperature = t;
+        } else if let Ok(p) = super::Pressure::try_from(name.as_str()) {
+            owserver.pressure = p;
+        } else {
+            return Err(OwError::Input(format!("Unknown scale {}", name)));
+        }
+    }
+    Ok(())
+}
+
+// test for (and remove) any of several spellings of a flag
+fn contains_any(args: &mut Arguments, keys: &[&'static str]) -> bool {
+    let mut found = false;
+    for key in keys {
+        if args.contains(*key) {
+            found = true;
+        }
+    }
+    found
+}
+
+// byte count or position, decimal or 0x hex
+fn number_match(s: &str) -> OwEResult<u32> {
+    let n = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => s.parse(),
+    };
+    n.map_err(|_| OwError::Numeric(format!("Not a number of bytes: {}", s)))
+}
+
 fn format_match(s: &str) -> OwEResult<super::Format> {
     match s {
         "fi" => Ok(super::Format::FI),
@@ -635,23 +1129,23 @@ fn format_match(s: &str) -> OwEResult<super::Format> {
 mod tests {
     use super::*;
 
-    fn short(opt: &String) -> String {
+    fn short(opt: &str) -> String {
         let c = opt.chars().next().unwrap_or('X');
         format!("-{}", c)
     }
 
-    fn long(opt: &String) -> String {
+    fn long(opt: &str) -> String {
         format!("--{}", opt)
     }
 
     #[test]
     fn test_short() {
-        let r = short(&"Xxx".to_string());
+        let r = short("Xxx");
         assert_eq!(r, "-X");
     }
     #[test]
     fn test_long() {
-        let r = long(&"Xxx".to_string());
+        let r = long("Xxx");
         assert_eq!(r, "--Xxx");
     }
 
@@ -686,16 +1180,14 @@ mod tests {
             ("psi", crate::OwMessage::PRESSURE_PSI),
             ("persist", crate::OwMessage::PERSISTENCE),
         ] {
-            let test = ts.0.to_string();
-            for t in [long(&test)] {
-                let args: Vec<&str> = vec![&t];
-                let mut owserver = crate::new();
-                let prog = OwLib;
-                let _ = prog.vector_line(&mut owserver, args);
-                owserver.make_flags();
-                let result = owserver.flags & ts.1;
-                assert_eq!(result, ts.1);
-            }
+            let t = long(ts.0);
+            let args: Vec<&str> = vec![&t];
+            let mut owserver = crate::new();
+            let prog = OwLib;
+            let _ = prog.vector_line(&mut owserver, args);
+            owserver.make_flags();
+            let result = owserver.flags & ts.1;
+            assert_eq!(result, ts.1);
         }
     }
     #[test]
@@ -719,6 +1211,45 @@ mod tests {
         }
     }
     #[test]
+    fn temperature_spelling() {
+        for ts in [
+            ("-C", super::super::Temperature::CELSIUS),
+            ("--celsius", super::super::Temperature::CELSIUS),
+            ("--Celsius", super::super::Temperature::CELSIUS),
+            ("-F", super::super::Temperature::FARENHEIT),
+            ("--fahrenheit", super::super::Temperature::FARENHEIT),
+            ("--Fahrenheit", super::super::Temperature::FARENHEIT),
+            ("--farenheit", super::super::Temperature::FARENHEIT),
+            ("--Farenheit", super::super::Temperature::FARENHEIT),
+            ("-K", super::super::Temperature::KELVIN),
+            ("--kelvin", super::super::Temperature::KELVIN),
+            ("--Kelvin", super::super::Temperature::KELVIN),
+            ("-R", super::super::Temperature::RANKINE),
+            ("--rankine", super::super::Temperature::RANKINE),
+            ("--Rankine", super::super::Temperature::RANKINE),
+        ] {
+            let mut owserver = crate::new();
+            let paths = OwLib.vector_line(&mut owserver, vec![ts.0]).unwrap();
+            assert!(paths.is_empty(), "{} left as a path", ts.0);
+            assert_eq!(owserver.temperature, ts.1, "{}", ts.0);
+        }
+    }
+    #[test]
+    fn scale_test() {
+        let mut owserver = crate::new();
+        let paths = OwLib
+            .vector_line(&mut owserver, vec!["--scale", "Kelvin", "--scale", "psi"])
+            .unwrap();
+        assert!(paths.is_empty());
+        assert_eq!(owserver.temperature, super::super::Temperature::KELVIN);
+        assert_eq!(owserver.pressure, super::super::Pressure::PSI);
+
+        let mut owserver = crate::new();
+        assert!(OwLib
+            .vector_line(&mut owserver, vec!["--scale", "furlongs"])
+            .is_err());
+    }
+    #[test]
     fn noport_test() {
         let args: Vec<&str> = vec![];
         let mut owserver = crate::new();
@@ -746,6 +1277,270 @@ mod tests {
         assert_eq!(owserver.listener, Some("localhost:14304".to_string()));
     }
 
+    #[test]
+    fn protocol_version_test() {
+        let mut owserver = crate::new();
+        OwRead
+            .vector_line(&mut owserver, vec!["--protocol-version", "2"])
+            .unwrap();
+        assert_eq!(owserver.send_version, 2);
+
+        let mut owserver = crate::new();
+        assert!(OwRead
+            .vector_line(&mut owserver, vec!["--protocol-version", "40000"])
+            .is_err());
+        assert_eq!(owserver.send_version, 0);
+    }
+
+    #[test]
+    fn show_flags_test() {
+        let mut owserver = crate::new();
+        let paths = OwRead
+            .vector_line(
+                &mut owserver,
+                vec![
+                    "-F",
+                    "--psi",
+                    "--show-flags",
+                    "/10.67C6697351FF/temperature",
+                ],
+            )
+            .unwrap();
+        assert!(paths.is_empty());
+        assert_eq!(
+            owserver.flags,
+            crate::OwMessage::TEMPERATURE_F
+                | crate::OwMessage::PRESSURE_PSI
+                | crate::OwMessage::BUS_RET
+        );
+        assert_eq!(
+            flag_lines(owserver.flags),
+            vec![
+                "Flags 0x00110002",
+                "\ttemperature\tF",
+                "\tpressure\tpsi",
+                "\tformat\tf.i",
+                "\townet\t-",
+                "\tuncached\t-",
+                "\tsafemode\t-",
+                "\talias\t-",
+                "\tpersist\t-",
+                "\tbus_ret\tbus",
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_hex_test() {
+        let mut owserver = crate::new();
+        let _ = OwLib.vector_line(&mut owserver, vec!["--raw"]);
+        assert!(owserver.raw);
+        assert!(!owserver.hex);
+
+        let mut owserver = crate::new();
+        let _ = OwLib.vector_line(&mut owserver, vec!["--raw", "--hex"]);
+        assert!(!owserver.raw);
+        assert!(owserver.hex);
+    }
+
+    #[test]
+    fn human_test() {
+        let mut owserver = crate::new();
+        let _ = OwSize.vector_line(&mut owserver, vec!["/10.67C6697351FF/memory"]);
+        assert_eq!(owserver.show_size(2048), "2048");
+
+        let mut owserver = crate::new();
+        let _ = OwSize.vector_line(&mut owserver, vec!["--human", "/10.67C6697351FF/memory"]);
+        assert_eq!(owserver.show_size(2048), "2.0 KiB");
+    }
+
+    #[test]
+    fn address_case_test() {
+        let path = "/10.67c6697351ff/temperature";
+        let mut owserver = crate::new();
+        let _ = OwLib.vector_line(&mut owserver, vec!["--upcase", path]);
+        assert_eq!(owserver.query_path(path), "/10.67C6697351FF/temperature");
+
+        let mut owserver = crate::new();
+        let _ = OwLib.vector_line(&mut owserver, vec!["--downcase", path]);
+        assert_eq!(
+            owserver.query_path("/10.67C6697351FF/temperature"),
+            "/10.67c6697351ff/temperature"
+        );
+
+        let mut owserver = crate::new();
+        let _ = OwLib.vector_line(&mut owserver, vec!["--upcase", "--downcase", path]);
+        assert_eq!(owserver.address_case, crate::message::AddressCase::UPPER);
+    }
+
+    #[test]
+    fn heartbeat_test() {
+        let mut owserver = crate::new();
+        let _ = OwSnoop.vector_line(&mut owserver, vec!["-p", "localhost:14304"]);
+        assert_eq!(owserver.heartbeat, None);
+
+        let mut owserver = crate::new();
+        let _ = OwSnoop.vector_line(&mut owserver, vec!["--heartbeat", "30"]);
+        assert_eq!(owserver.heartbeat, Some(std::time::Duration::from_secs(30)));
+
+        let mut owserver = crate::new();
+        assert!(OwSnoop
+            .vector_line(&mut owserver, vec!["--heartbeat", "0"])
+            .is_err());
+    }
+
+    #[test]
+    fn timeout_connect_test() {
+        let mut owserver = crate::new();
+        let _ = OwGet.vector_line(&mut owserver, vec!["--timeout-connect", "0.5"]);
+        assert_eq!(
+            owserver.stream.connect_timeout(),
+            std::time::Duration::from_millis(500)
+        );
+        assert_eq!(
+            owserver.stream.read_timeout(),
+            std::time::Duration::from_secs(5)
+        );
+
+        let mut owserver = crate::new();
+        assert!(OwGet
+            .vector_line(&mut owserver, vec!["--timeout-connect", "-1"])
+            .is_err());
+    }
+
+    #[test]
+    fn epoch_test() {
+        let mut owserver = crate::new();
+        let _ = OwRead.vector_line(
+            &mut owserver,
+            vec!["--epoch", "/10.67C6697351FF/temperature"],
+        );
+        assert!(owserver.timestamp && owserver.epoch);
+
+        let mut owserver = crate::new();
+        let _ = OwRead.vector_line(
+            &mut owserver,
+            vec!["--timestamp", "/10.67C6697351FF/temperature"],
+        );
+        assert!(owserver.timestamp && !owserver.epoch);
+    }
+
+    #[test]
+    fn units_option_test() {
+        let mut owserver = crate::new();
+        let _ = OwGet.vector_line(
+            &mut owserver,
+            vec!["--units", "-F", "/10.67C6697351FF/temperature"],
+        );
+        assert_eq!(
+            owserver.show_units("/10.67C6697351FF/temperature", "85.75".to_string()),
+            "85.75 °F"
+        );
+    }
+
+    #[test]
+    fn size_offset_test() {
+        let mut owserver = crate::new();
+        let _ = OwRead.vector_line(
+            &mut owserver,
+            vec![
+                "--offset",
+                "0x20",
+                "--size",
+                "32",
+                "/23.5F2F2F2F2F2F/memory",
+            ],
+        );
+        assert_eq!(owserver.offset, 32);
+        assert_eq!(owserver.size, 32);
+
+        let mut owserver = crate::new();
+        let result = OwRead.vector_line(&mut owserver, vec!["--offset", "0xZZ"]);
+        assert!(matches!(result, Err(OwError::Numeric(_))));
+        let result = OwRead.vector_line(&mut owserver, vec!["--size", "-1"]);
+        assert!(matches!(result, Err(OwError::Numeric(_))));
+    }
+
+    #[test]
+    fn cache_ttl_test() {
+        let mut owserver = crate::new();
+        let _ = OwRead.vector_line(
+            &mut owserver,
+            vec!["--cache-ttl", "2", "/10.67C6697351FF/temperature"],
+        );
+        assert_eq!(
+            owserver.read_cache.ttl(),
+            Some(std::time::Duration::from_secs(2))
+        );
+
+        let mut owserver = crate::new();
+        assert!(OwRead
+            .vector_line(&mut owserver, vec!["--cache-ttl", "nan"])
+            .is_err());
+    }
+
+    #[test]
+    fn dir_cache_ttl_test() {
+        let mut owserver = crate::new();
+        let _ = OwDir.vector_line(&mut owserver, vec!["/"]);
+        assert_eq!(owserver.dir_cache.ttl(), None);
+
+        let mut owserver = crate::new();
+        let _ = OwTree.vector_line(&mut owserver, vec!["--dir-cache-ttl", "1.5"]);
+        assert_eq!(
+            owserver.dir_cache.ttl(),
+            Some(std::time::Duration::from_millis(1500))
+        );
+
+        let mut owserver = crate::new();
+        assert!(OwDir
+            .vector_line(&mut owserver, vec!["--dir-cache-ttl", "-1"])
+            .is_err());
+    }
+
+    #[test]
+    fn no_bus_test() {
+        let mut owserver = crate::new();
+        let _ = OwDir.vector_line(&mut owserver, vec!["--bare", "/"]);
+        assert!(owserver.bare && !owserver.no_bus);
+
+        let mut owserver = crate::new();
+        let _ = OwDir.vector_line(&mut owserver, vec!["--no-bus", "/"]);
+        assert!(!owserver.bare && owserver.no_bus);
+        assert_eq!(owserver.flags & crate::OwMessage::BUS_RET, 0);
+    }
+
+    #[test]
+    fn color_test() {
+        let mut owserver = crate::new();
+        let _ = OwTree.vector_line(&mut owserver, vec!["--color", "always"]);
+        assert!(owserver.color());
+
+        let mut owserver = crate::new();
+        let _ = OwTree.vector_line(&mut owserver, vec!["--color", "never"]);
+        assert!(!owserver.color());
+
+        let mut owserver = crate::new();
+        let result = OwTree.vector_line(&mut owserver, vec!["--color", "sometimes"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn style_test() {
+        let mut owserver = crate::new();
+        let _ = OwTree.vector_line(&mut owserver, vec![]);
+        assert_eq!(*owserver.tree_style(), crate::message::TreeStyle::UNICODE);
+
+        let mut owserver = crate::new();
+        let _ = OwTree.vector_line(&mut owserver, vec!["--style", "ASCII"]);
+        assert_eq!(*owserver.tree_style(), crate::message::TreeStyle::ASCII);
+
+        let mut owserver = crate::new();
+        assert!(OwTree
+            .vector_line(&mut owserver, vec!["--style", "fancy"])
+            .is_err());
+    }
+
     fn has_help<P: Parser>(prog: P) {
         let mut owserver = crate::new();
         let result = prog.vector_line(&mut owserver, vec!["-h"]);
@@ -763,6 +1558,90 @@ mod tests {
         has_help(OwSize);
         has_help(OwSnoop);
         has_help(OwTree);
+        has_help(OwProbe);
+        has_help(OwFormat);
+    }
+
+    #[test]
+    fn unknown_option_test() {
+        let mut owserver = crate::new();
+        let result = OwRead.vector_line(
+            &mut owserver,
+            vec!["--hexx", "/10.67C6697351FF/temperature"],
+        );
+        assert!(matches!(result, Err(OwError::Input(ref e)) if e.contains("--hexx")));
+
+        // options for other programs are unknown too
+        let mut owserver = crate::new();
+        assert!(OwRead.vector_line(&mut owserver, vec!["--bare"]).is_err());
+
+        // negative values are not options
+        let mut owserver = crate::new();
+        let paths = OwWrite
+            .vector_line(&mut owserver, vec!["/10.67C6697351FF/templow", "-5"])
+            .unwrap();
+        assert_eq!(paths, vec!["/10.67C6697351FF/templow", "-5"]);
+    }
+
+    #[test]
+    fn min_interval_test() {
+        let mut owserver = crate::new();
+        let _ = OwRead.vector_line(&mut owserver, vec!["/10.67C6697351FF/temperature"]);
+        assert!(owserver.min_interval.is_zero());
+        let _ = OwRead.vector_line(
+            &mut owserver,
+            vec!["--min-interval", "250", "/10.67C6697351FF/temperature"],
+        );
+        assert_eq!(owserver.min_interval, std::time::Duration::from_millis(250));
+    }
+
+    #[test]
+    fn interval_test() {
+        let mut owserver = crate::new();
+        OwRead.vector_line(&mut owserver, vec![]).unwrap();
+        assert!(owserver.interval.is_none());
+
+        let mut owserver = crate::new();
+        let paths = OwRead
+            .vector_line(&mut owserver, vec!["--interval", "2.5", "--interval-align"])
+            .unwrap();
+        assert!(paths.is_empty());
+        assert_eq!(
+            owserver.interval,
+            Some(std::time::Duration::from_millis(2500))
+        );
+        assert!(owserver.align);
+
+        let mut owserver = crate::new();
+        assert!(OwRead
+            .vector_line(&mut owserver, vec!["--interval", "0"])
+            .is_err());
+    }
+
+    #[test]
+    fn version_test() {
+        let mut owserver = crate::new();
+        // paths are not gathered once --version is seen
+        let paths = OwRead
+            .vector_line(
+                &mut owserver,
+                vec!["--version", "/10.67C6697351FF/temperature"],
+            )
+            .unwrap();
+        assert!(paths.is_empty());
+
+        let mut owserver = crate::new();
+        assert!(OwDir
+            .vector_line(&mut owserver, vec!["-V"])
+            .unwrap()
+            .is_empty());
+
+        let lines = version_lines();
+        assert_eq!(
+            lines[0],
+            format!("owrust version {}", env!("CARGO_PKG_VERSION"))
+        );
+        assert_eq!(lines[1], "owserver protocol version 0");
     }
 
     fn has_server<P: Parser>(prog: P) {
@@ -782,5 +1661,6 @@ mod tests {
         has_server(OwSize);
         has_server(OwSnoop);
         has_server(OwTree);
+        has_server(OwProbe);
     }
 }
@@ -0,0 +1,174 @@
+//! **owrust** Rust library interfaces with owserver to use 1-wire devices
+//!
+//! This is a tool in the 1-wire file system **OWFS**
+//!
+//! This library is the central part of **owrust** -- the _rust language_ OWFS programs
+//! * **OWFS** [documentation](https://owfs.org) and [code](https://github.com/owfs/owfs)
+//! * **owrust** [repository](https://github.com/alfille/owrust)
+//!
+//! ## PURPOSE
+//! Poll paces repeated readings (the _--interval_ option)
+//! * fixed cadence, without drift from the time readings take
+//! * optionally aligned to wall-clock boundaries (_--interval-align_)
+//!   * e.g. every 10 seconds at :00, :10, :20 ...
+//!   * nicer for time-series databases
+
+// owrust project
+// https://github.com/alfille/owrust
+//
+// This is a Rust version of my C owfs code for talking to 1-wire devices via owserver
+// Basically owserver can talk to the physical devices, and provides network access via my "owserver protocol"
+//
+// MIT Licence
+// {c} 2025 Paul H Alfille
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// ### Poll
+/// Wait between repeated readings
+/// * call **wait** before each round of readings
+#[derive(Debug, Clone)]
+pub struct Poll {
+    interval: Duration,
+    align: bool,
+    next: Option<SystemTime>,
+}
+
+impl Poll {
+    /// ### new
+    /// * interval -- time between rounds of readings
+    /// * align -- start on a wall-clock multiple of interval
+    pub fn new(interval: Duration, align: bool) -> Self {
+        Poll {
+            interval,
+            align,
+            next: None,
+        }
+    }
+
+    /// ### wait
+    /// Sleep until the next round of readings is due
+    /// * first round is immediate, or at the next boundary if aligned
+    /// * later rounds follow the cadence
+    ///   * rounds missed (slow readings) are skipped
+    pub fn wait(&mut self) {
+        thread::sleep(self.delay());
+    }
+
+    /// ### wait_unless
+    /// **wait**, but give up as soon as stop is set
+    /// * returns `false` if stopped
+    pub fn wait_unless(&mut self, stop: &AtomicBool) -> bool {
+        let delay = self.delay();
+        let start = std::time::Instant::now();
+        while !stop.load(Ordering::Relaxed) {
+            let elapsed = start.elapsed();
+            if elapsed >= delay {
+                return true;
+            }
+            thread::sleep((delay - elapsed).min(Poll::STOP_CHECK));
+        }
+        false
+    }
+
+    // How often wait_unless looks at the stop flag
+    const STOP_CHECK: Duration = Duration::from_millis(20);
+
+    // time until the next round is due (and move on the cadence)
+    fn delay(&mut self) -> Duration {
+        let now = SystemTime::now();
+        let due = match self.next {
+            None if self.align => next_boundary(now, self.interval),
+            None => now,
+            Some(mut due) => {
+                while due < now && !self.interval.is_zero() {
+                    due += self.interval;
+                }
+                due
+            }
+        };
+        self.next = Some(due + self.interval);
+        due.duration_since(now).unwrap_or_default()
+    }
+}
+
+/// ### next_boundary
+/// First wall-clock multiple of interval after now
+/// * counted from 1970-01-01 UTC
+/// * a time already on a boundary is returned unchanged
+pub fn next_boundary(now: SystemTime, interval: Duration) -> SystemTime {
+    let step = interval.as_nanos();
+    if step == 0 {
+        return now;
+    }
+    let since = now
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let next = since.div_ceil(step) * step;
+    now + Duration::from_nanos((next - since) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: u64, millis: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs) + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn boundary_test() {
+        let ten = Duration::from_secs(10);
+        assert_eq!(
+            next_boundary(at(1_748_781_003, 0), ten),
+            at(1_748_781_010, 0)
+        );
+        assert_eq!(
+            next_boundary(at(1_748_781_009, 999), ten),
+            at(1_748_781_010, 0)
+        );
+        assert_eq!(
+            next_boundary(at(1_748_781_010, 0), ten),
+            at(1_748_781_010, 0)
+        );
+        assert_eq!(
+            next_boundary(at(1_748_781_010, 1), ten),
+            at(1_748_781_020, 0)
+        );
+        assert_eq!(
+            next_boundary(at(1_748_781_003, 0), Duration::from_secs(60)),
+            at(1_748_781_060, 0)
+        );
+        assert_eq!(
+            next_boundary(at(100, 250), Duration::from_millis(500)),
+            at(100, 500)
+        );
+        assert_eq!(next_boundary(at(7, 3), Duration::ZERO), at(7, 3));
+    }
+
+    #[test]
+    fn cadence_test() {
+        let mut poll = Poll::new(Duration::from_millis(20), false);
+        let start = SystemTime::now();
+        poll.wait();
+        poll.wait();
+        poll.wait();
+        let elapsed = SystemTime::now().duration_since(start).unwrap();
+        assert!(elapsed >= Duration::from_millis(40));
+    }
+
+    #[test]
+    fn wait_unless_test() {
+        let stop = AtomicBool::new(false);
+        let mut poll = Poll::new(Duration::from_secs(10), false);
+        // first round is immediate
+        assert!(poll.wait_unless(&stop));
+        stop.store(true, Ordering::Relaxed);
+        let start = std::time::Instant::now();
+        assert!(!poll.wait_unless(&stop));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -102,12 +102,17 @@ pub trait PrintMessage {
             self.string_offset()
         )
     }
+    // path without its nul terminator
     fn string_path(&self) -> String {
-        String::from_utf8_lossy(self.content()).to_string()
+        String::from_utf8_lossy(self.content())
+            .trim_end_matches('\0')
+            .to_string()
     }
     fn string_path_pair(&self) -> (String, String) {
         let path_len: usize = (self.payload() - (self.size() as i32)) as usize;
-        let first: String = String::from_utf8_lossy(&self.content()[..path_len]).to_string();
+        let first: String = String::from_utf8_lossy(&self.content()[..path_len])
+            .trim_end_matches('\0')
+            .to_string();
         let second: String = self.content()[path_len..self.payload() as usize]
             .iter()
             .map(|b| format!("{:02X}", b))
@@ -37,7 +37,7 @@
 // {c} 2025 Paul H Alfille
 
 use std::ffi;
-use std::io::{Read, Write};
+use std::io::Write;
 use std::net::TcpStream;
 use std::str;
 
@@ -67,7 +67,7 @@ pub(super) struct OwQuery {
 
 impl OwQuery {
     // Default owserver version (to owserver)
-    const SENDVERSION: u32 = 0;
+    pub(super) const SENDVERSION: u32 = 0;
 
     // Maximum make_size of returned data (pretty arbitrary but matches C implementation)
     const DEFAULTSIZE: u32 = 65536;
@@ -117,7 +117,7 @@ impl OwQuery {
     fn add_path(&mut self, path: &str) -> OwEResult<()> {
         // Add nul-terminated path (and includes null in payload size)
         let s = ffi::CString::new(path)?;
-        self.content = s.as_bytes().to_vec();
+        self.content = s.as_bytes_with_nul().to_vec();
         self.payload = self.content.len() as i32;
         Ok(())
     }
@@ -147,7 +147,7 @@ impl OwQuery {
         static HSIZE: usize = 24;
         let mut buffer: [u8; HSIZE] = [0; HSIZE];
 
-        stream.read_exact(&mut buffer)?;
+        super::read_exactly(stream, &mut buffer, "header")?;
         let mut rcv = OwQuery {
             version: u32::from_be_bytes(buffer[0..4].try_into().unwrap()),
             payload: i32::from_be_bytes(buffer[4..8].try_into().unwrap()),
@@ -160,12 +160,19 @@ impl OwQuery {
         };
 
         // read payload
+        if rcv.payload > crate::message::MAX_PAYLOAD {
+            return Err(OwError::Output(format!(
+                "Query payload of {} bytes is too large (most {})",
+                rcv.payload,
+                crate::message::MAX_PAYLOAD
+            )));
+        }
         if rcv.payload > 0 {
             // create Vec with just the right size (based on payload)
             rcv.content = Vec::with_capacity(rcv.payload as usize);
             rcv.content.resize(rcv.payload as usize, 0);
 
-            stream.read_exact(&mut rcv.content)?;
+            super::read_exactly(stream, &mut rcv.content, "payload")?;
         }
 
         // read tokens
@@ -173,14 +180,14 @@ impl OwQuery {
             let toks = rcv.version & crate::message::SERVERTOKENS;
             for _ in 0..toks {
                 let mut tok: Token = [0u8; 16];
-                stream.read_exact(&mut tok)?;
+                super::read_exactly(stream, &mut tok, "token")?;
                 rcv.tokenlist.push(tok)
             }
         }
 
         // test token
         if rcv.tokenlist.contains(&token) {
-            return Err(OwError::General("Loop in owserver topology".to_string()));
+            return Err(OwError::general("Loop in owserver topology"));
         }
 
         // Add our token
@@ -215,6 +222,16 @@ impl OwQuery {
     /// * Will include tokens when available
     /// * own token included
     pub(super) fn send(&mut self, stream: &mut TcpStream) -> OwEResult<()> {
+        // Write to network
+        stream.write_all(&self.to_bytes())?;
+        Ok(())
+    }
+
+    // Wire layout of a query
+    // * header: 6 big-endian 32 bit words (version, payload, type, flags, size, offset)
+    // * payload bytes (path and data)
+    // * tokens: each 16 bytes as-is, in the order added (count is in the version word)
+    fn to_bytes(&self) -> Vec<u8> {
         let mut msg: Vec<u8> = [
             self.version,
             self.payload as u32,
@@ -229,21 +246,27 @@ impl OwQuery {
         if self.payload > 0 {
             msg.extend_from_slice(&self.content);
         }
-        // use bytemuck to reinterpret sequential lumps of bytes to sequential bytes
-        msg.extend_from_slice(bytemuck::cast_slice(&self.tokenlist));
-
-        // Write to network
-        stream.write_all(&msg)?;
-        Ok(())
+        msg.extend(self.tokenlist.iter().flatten());
+        msg
     }
     pub fn add_token(&mut self, token: Token) {
         let toks = match self.version & crate::message::SERVERMESSAGE {
             crate::message::SERVERMESSAGE => self.version & crate::message::SERVERTOKENS,
             _ => 0,
         };
-        self.version = crate::message::SERVERMESSAGE | (toks + 1);
+        let protocol =
+            self.version & !(crate::message::SERVERMESSAGE | crate::message::SERVERTOKENS);
+        self.version = protocol | crate::message::SERVERMESSAGE | (toks + 1);
         self.tokenlist.push(token);
     }
+
+    /// ### set_protocol
+    /// protocol version in the upper bits of the version word
+    /// * server message flag and token count are kept
+    pub(super) fn set_protocol(&mut self, protocol: u32) {
+        let keep = crate::message::SERVERMESSAGE | crate::message::SERVERTOKENS;
+        self.version = (self.version & keep) | (protocol << crate::message::SERVERPROTOCOL_SHIFT);
+    }
 }
 
 impl PrintMessage for OwQuery {
@@ -278,9 +301,27 @@ mod tests {
 
     #[test]
     fn test_blank_query() {
-        let query =
-            OwQuery::new(0x10101010 as u32, OwQuery::READ, Some("/"), None, [0u8; 16]).unwrap();
+        let query = OwQuery::new(0x10101010, OwQuery::READ, Some("/"), None, [0u8; 16]).unwrap();
         let desc = query.print_all("Test Query").join("\n").to_string();
-        assert_eq!( desc, "Test Query  Version: 10001 tokens=1\nReturn code = 2\nFlags: C psi f.i   safe   \nPayload:1 Size:65536 Offset:0\n".to_string() );
+        assert_eq!( desc, "Test Query Version: 10001 tokens=1\nREAD /\nFlags: C psi f.i safe\nPayload:2 Size:65536 Offset:0\n".to_string() );
+    }
+
+    #[test]
+    fn wire_tokens_test() {
+        let first: Token = core::array::from_fn(|i| i as u8);
+        let second: Token = [0xA5; 16];
+        let mut query = OwQuery::new(0, OwQuery::NOP, None, None, first).unwrap();
+        query.add_token(second);
+        let bytes = query.to_bytes();
+
+        // header: version has the server message bit and 2 tokens
+        assert_eq!(bytes.len(), 24 + 32);
+        assert_eq!(
+            bytes[0..4],
+            (crate::message::SERVERMESSAGE | 2).to_be_bytes()
+        );
+        // tokens follow the header unchanged, in order
+        assert_eq!(bytes[24..40], first);
+        assert_eq!(bytes[40..56], second);
     }
 }
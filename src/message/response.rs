@@ -36,15 +36,16 @@
 // MIT Licence
 // {c} 2025 Paul H Alfille
 
-pub use crate::error::OwEResult;
+pub use crate::error::{OwEResult, OwError};
 use crate::message::print_message::PrintMessage;
-use std::io::{Read, Write};
+use std::io::Write;
 use std::net::TcpStream;
 
 /// message with answers
 /// * header (24 bytes) and content
 /// * differs from query in **ret** value rather than **message type**
-#[derive(Debug, PartialEq, Clone)]
+/// * comparable (e.g. in tests) -- see also **header_eq**
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub(super) struct OwResponse {
     pub(super) version: u32,
     pub(super) payload: i32,
@@ -68,6 +69,21 @@ impl OwResponse {
         }
     }
 
+    /// ### header_eq
+    /// Same header fields, ignoring the token count
+    /// * token count (low bits of version) depends on the owserver path taken
+    /// * content is not compared
+    #[cfg(test)]
+    pub(super) fn header_eq(&self, other: &OwResponse) -> bool {
+        let tokenless = !crate::message::SERVERTOKENS;
+        (self.version & tokenless) == (other.version & tokenless)
+            && self.payload == other.payload
+            && self.ret == other.ret
+            && self.flags == other.flags
+            && self.size == other.size
+            && self.offset == other.offset
+    }
+
     /// ### get_plus_ping
     /// Get a RESPONSE message from the network and parse it:
     /// * read header ( 6 words), translated from network order
@@ -78,7 +94,7 @@ impl OwResponse {
         let mut buffer: [u8; HSIZE] = [0; HSIZE];
 
         // Take first 24 bytes of buffer to fill header
-        stream.read_exact(&mut buffer)?;
+        super::read_exactly(stream, &mut buffer, "header")?;
         let mut rcv = OwResponse {
             version: u32::from_be_bytes(buffer[0..4].try_into().unwrap()),
             payload: i32::from_be_bytes(buffer[4..8].try_into().unwrap()),
@@ -90,12 +106,19 @@ impl OwResponse {
         };
 
         // read payload
+        if rcv.payload > crate::message::MAX_PAYLOAD {
+            return Err(OwError::Output(format!(
+                "Response payload of {} bytes is too large (most {})",
+                rcv.payload,
+                crate::message::MAX_PAYLOAD
+            )));
+        }
         if rcv.payload > 0 {
             // create Vec with just the right size (based on payload)
             rcv.content = Vec::with_capacity(rcv.payload as usize);
             rcv.content.resize(rcv.payload as usize, 0);
 
-            stream.read_exact(&mut rcv.content)?;
+            super::read_exactly(stream, &mut rcv.content, "payload")?;
         }
 
         Ok(rcv)
@@ -174,8 +197,95 @@ mod tests {
     use super::*;
     #[test]
     fn test_blank_response() {
-        let resp = OwResponse::new(0x10101010 as u32);
+        let resp = OwResponse::new(0x10101010);
         let desc = resp.print_all("Test Response").join("\n").to_string();
-        assert_eq!( desc, "Test Response  Version: 1\nUNKNOWN message number 0\nFlags: C psi f.i   safe   \nPayload:0 Size:0 Offset:0\n".to_string() );
+        assert_eq!( desc, "Test Response Version: 1\nReturn code = 0\nFlags: C psi f.i safe\nPayload:0 Size:0 Offset:0\n".to_string() );
+    }
+
+    #[test]
+    fn huge_payload_test() {
+        use std::net::TcpListener;
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (mut server, _) = listener.accept().unwrap();
+
+        // header claims a 2GB payload, none follows
+        let header: Vec<u8> = [0u32, 0x7FFF_FFFF, 0, 0, 0, 0]
+            .iter()
+            .flat_map(|u| u.to_be_bytes())
+            .collect();
+        server.write_all(&header).unwrap();
+        match OwResponse::get(&mut client) {
+            Err(OwError::Output(e)) => assert!(e.contains("too large")),
+            other => panic!("Expected payload error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn equal_response() {
+        let make = || {
+            let mut resp = OwResponse::new(0x0001_0002);
+            resp.version = crate::message::SERVERMESSAGE | 1;
+            resp.ret = 12;
+            resp.payload = 12;
+            resp.size = 12;
+            resp.content = b"      85.75\0".to_vec();
+            resp
+        };
+        let first = make();
+        let mut second = make();
+        assert_eq!(first, second);
+        assert!(first.header_eq(&second));
+
+        // relayed through 2 owservers
+        second.version = crate::message::SERVERMESSAGE | 2;
+        assert_ne!(first, second);
+        assert!(first.header_eq(&second));
+
+        second.ret = -2;
+        assert!(!first.header_eq(&second));
+    }
+
+    #[test]
+    fn truncated_response() {
+        use std::net::TcpListener;
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            // header promises 100 payload bytes, only 10 follow
+            let header: Vec<u8> = [0u32, 100, 0, 0, 100, 0]
+                .iter()
+                .flat_map(|u| u.to_be_bytes())
+                .collect();
+            stream.write_all(&header).unwrap();
+            stream.write_all(&[b'x'; 10]).unwrap();
+        });
+        let mut stream = TcpStream::connect(address).unwrap();
+        match OwResponse::get(&mut stream) {
+            Err(crate::error::OwError::Output(e)) => {
+                assert!(e.starts_with("truncated owserver response"), "{}", e);
+                assert!(e.contains("expected 100 bytes, received 10"), "{}", e);
+            }
+            other => panic!("Expected truncation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn closed_response() {
+        use std::net::TcpListener;
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            // hang up without a word
+            let _ = listener.accept().unwrap();
+        });
+        let mut stream = TcpStream::connect(address).unwrap();
+        match OwResponse::get(&mut stream) {
+            Err(crate::error::OwError::Io(e)) => {
+                assert_eq!(e.kind(), std::io::ErrorKind::UnexpectedEof);
+            }
+            other => panic!("Expected connection closed, got {:?}", other),
+        }
     }
 }
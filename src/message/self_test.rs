@@ -0,0 +1,86 @@
+//! **owrust** Rust library interfaces with owserver to use 1-wire devices
+//!
+//! This is a tool in the 1-wire file system **OWFS**
+//!
+//! This library is the central part of **owrust** -- the _rust language_ OWFS programs
+//! * **OWFS** [documentation](https://owfs.org) and [code](https://github.com/owfs/owfs)
+//! * **owrust** [repository](https://github.com/alfille/owrust)
+//!
+//! ## PURPOSE
+//! SelfTest is the report of a quick owserver health check
+//! * filled in by **OwMessage::self_test**
+//! * answers "is my setup working?" in one call
+
+// owrust project
+// https://github.com/alfille/owrust
+//
+// This is a Rust version of my C owfs code for talking to 1-wire devices via owserver
+// Basically owserver can talk to the physical devices, and provides network access via my "owserver protocol"
+//
+// MIT Licence
+// {c} 2025 Paul H Alfille
+
+use std::fmt;
+
+/// ### SelfTest
+/// Which owserver capabilities worked
+/// * ping -- owserver answers a NOP message
+/// * root_dir -- root directory can be listed
+/// * version -- owserver program version can be read
+/// * present -- root directory reported present
+/// * problems -- description of each failure
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SelfTest {
+    pub ping: bool,
+    pub root_dir: bool,
+    pub version: bool,
+    pub present: bool,
+    pub problems: Vec<String>,
+}
+
+impl SelfTest {
+    /// ### healthy
+    /// every check passed
+    pub fn healthy(&self) -> bool {
+        self.ping && self.root_dir && self.version && self.present
+    }
+}
+
+impl fmt::Display for SelfTest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let checks = [
+            ("ping", self.ping),
+            ("root directory", self.root_dir),
+            ("version", self.version),
+            ("present", self.present),
+        ];
+        for (name, ok) in checks {
+            writeln!(f, "{:<16}{}", name, if ok { "ok" } else { "FAILED" })?;
+        }
+        for problem in self.problems.iter() {
+            writeln!(f, "  {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_test() {
+        let report = SelfTest {
+            ping: true,
+            root_dir: true,
+            version: false,
+            present: true,
+            problems: vec!["version: no value".to_string()],
+        };
+        assert!(!report.healthy());
+        assert_eq!(
+            report.to_string(),
+            "ping            ok\nroot directory  ok\nversion         FAILED\npresent         ok\n  version: no value\n"
+        );
+    }
+}
@@ -37,23 +37,60 @@
 // {c} 2025 Paul H Alfille
 
 use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use crate::error::{OwEResult, OwError};
 use crate::message::query::OwQuery;
+use crate::message::token::make_token;
+use crate::message::Token;
 
 use crate::console::console_lines;
 use crate::message::print_message::PrintMessage;
 
 use crate::OwMessage;
 
+/// Tokens of all the live instances of one relay (for loop detection)
+pub(super) type RelayTokens = Arc<Mutex<Vec<Token>>>;
+
 pub(super) struct OwServerInstance {
     message: crate::OwMessage,
     stream_in: TcpStream,
+    token: Token,
+    relay_tokens: RelayTokens,
 }
 impl OwServerInstance {
-    pub(super) fn new(message: crate::OwMessage, stream_in: TcpStream) -> OwServerInstance {
-        OwServerInstance { message, stream_in }
+    // each instance (connection) gets its own token, registered with the relay
+    pub(super) fn new(
+        message: crate::OwMessage,
+        stream_in: TcpStream,
+        relay_tokens: RelayTokens,
+    ) -> OwServerInstance {
+        let token = make_token();
+        if let Ok(mut tokens) = relay_tokens.lock() {
+            tokens.push(token);
+        }
+        OwServerInstance {
+            message,
+            stream_in,
+            token,
+            relay_tokens,
+        }
+    }
+
+    // incoming query, with our token added
+    // * a loop if it already passed through this relay (any instance)
+    fn read_query(&mut self) -> OwEResult<OwQuery> {
+        let rcv = OwQuery::get(&mut self.stream_in, self.token)?;
+        let earlier = &rcv.tokenlist[..rcv.tokenlist.len() - 1];
+        if let Ok(tokens) = self.relay_tokens.lock() {
+            if earlier.iter().any(|t| tokens.contains(t)) {
+                return Err(OwError::general("Loop in owserver topology"));
+            }
+        }
+        Ok(rcv)
     }
+
     pub(super) fn handle_query(&mut self) {
         // Set timeout
         match self
@@ -68,8 +105,10 @@ impl OwServerInstance {
         }
 
         // get Query
-        let mut rcv = match OwQuery::get(&mut self.stream_in, self.message.token) {
+        let mut rcv = match self.read_query() {
             Ok(r) => r,
+            // client hung up (normal end of a connection)
+            Err(OwError::Io(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => return,
             Err(e) => {
                 eprintln!("Could not read a packet. {}", e);
                 return;
@@ -100,5 +139,98 @@ impl OwServerInstance {
                 }
             }
         }
+        self.message.stream.finish();
+    }
+}
+
+// instance token is no longer in use
+impl Drop for OwServerInstance {
+    fn drop(&mut self) {
+        if let Ok(mut tokens) = self.relay_tokens.lock() {
+            tokens.retain(|t| *t != self.token);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::tests::{mock_client, mock_owserver, reply};
+    use std::net::TcpListener;
+
+    // instance for a new connection, and the client end of that connection
+    fn connected(relay_tokens: &RelayTokens) -> (OwServerInstance, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream_in, _) = listener.accept().unwrap();
+        let instance = OwServerInstance::new(OwMessage::new(), stream_in, relay_tokens.clone());
+        (instance, client)
+    }
+
+    fn query(tokens: &[Token]) -> OwQuery {
+        let mut q = OwQuery::new(0, OwQuery::READ, Some("/"), None, make_token()).unwrap();
+        for t in tokens {
+            q.add_token(*t);
+        }
+        q
+    }
+
+    #[test]
+    fn instance_token_test() {
+        let relay_tokens: RelayTokens = Arc::new(Mutex::new(Vec::new()));
+        let (mut first, mut first_client) = connected(&relay_tokens);
+        let (mut second, mut second_client) = connected(&relay_tokens);
+        assert_ne!(first.token, second.token);
+        assert_ne!(first.token, first.message.token);
+        assert_eq!(relay_tokens.lock().unwrap().len(), 2);
+
+        // fresh query is relayed with the instance token
+        query(&[]).send(&mut first_client).unwrap();
+        let q = first.read_query().unwrap();
+        assert_eq!(q.tokenlist.last(), Some(&first.token));
+
+        // query that went through the first instance comes back
+        query(&[first.token]).send(&mut first_client).unwrap();
+        assert!(first.read_query().is_err());
+        query(&[first.token]).send(&mut second_client).unwrap();
+        assert!(matches!(second.read_query(), Err(OwError::General(e)) if e.contains("Loop")));
+
+        drop(first);
+        assert_eq!(*relay_tokens.lock().unwrap(), vec![second.token]);
+    }
+
+    #[test]
+    fn relay_tokens_test() {
+        // upstream owserver records the tokens it was sent
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let s = seen.clone();
+        let upstream = mock_owserver(move |q| {
+            s.lock().unwrap().push(q.tokenlist.clone());
+            vec![reply(0, b"85.75")]
+        });
+        let relay_tokens: RelayTokens = Arc::new(Mutex::new(Vec::new()));
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream_in, _) = listener.accept().unwrap();
+        let mut instance = OwServerInstance::new(mock_client(&upstream), stream_in, relay_tokens);
+
+        // query that already passed through two owservers
+        let (first, second) = (make_token(), make_token());
+        let mut q = OwQuery::new(
+            0,
+            OwQuery::READ,
+            Some("/10.67C6697351FF/temperature"),
+            None,
+            first,
+        )
+        .unwrap();
+        q.add_token(second);
+        q.send(&mut client).unwrap();
+        instance.handle_query();
+
+        // earlier tokens, then the instance's own, then upstream's
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0][..3], [first, second, instance.token]);
     }
 }
@@ -0,0 +1,64 @@
+//! **owrust** Rust library interfaces with owserver to use 1-wire devices
+//!
+//! This is a tool in the 1-wire file system **OWFS**
+//!
+//! This library is the central part of **owrust** -- the _rust language_ OWFS programs
+//! * **OWFS** [documentation](https://owfs.org) and [code](https://github.com/owfs/owfs)
+//! * **owrust** [repository](https://github.com/alfille/owrust)
+//!
+//! ## PURPOSE
+//! ServerInfo is a one-call diagnostic summary of an owserver
+//! * filled in by **OwMessage::server_info**
+//! * handy for status tools and bug reports
+
+// owrust project
+// https://github.com/alfille/owrust
+//
+// This is a Rust version of my C owfs code for talking to 1-wire devices via owserver
+// Basically owserver can talk to the physical devices, and provides network access via my "owserver protocol"
+//
+// MIT Licence
+// {c} 2025 Paul H Alfille
+
+use std::fmt;
+
+/// ### ServerInfo
+/// Summary of an owserver
+/// * version -- protocol version word of the last response
+/// * pid -- owserver process id (from `/system/process/pid`)
+/// * devices -- number of 1-wire devices in the root directory
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ServerInfo {
+    pub version: Option<u32>,
+    pub pid: u32,
+    pub devices: usize,
+}
+
+impl fmt::Display for ServerInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.version {
+            Some(v) => write!(f, "version {:08X}", v)?,
+            None => write!(f, "version -")?,
+        }
+        write!(f, " pid {} devices {}", self.pid, self.devices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_test() {
+        let info = ServerInfo {
+            version: Some(0x0001_0000),
+            pid: 1234,
+            devices: 3,
+        };
+        assert_eq!(info.to_string(), "version 00010000 pid 1234 devices 3");
+        assert_eq!(
+            ServerInfo::default().to_string(),
+            "version - pid 0 devices 0"
+        );
+    }
+}
@@ -10,6 +10,7 @@
 //! Stream encapsulates the connection to an owserver
 //! * handles the persistent connection request (where the Tcp connection is reused if possible for efficiency)
 //! * holds a target adress
+//! * optionally shares a pool of open connections between cloned Streams
 //!
 //! ## EXAMPLES
 //! * New connection
@@ -35,8 +36,8 @@
 // MIT Licence
 // {c} 2025 Paul H Alfille
 
-use std::io::Write;
-use std::net::TcpStream;
+use std::net::{Shutdown, SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 pub use crate::error::{OwEResult, OwError};
@@ -47,20 +48,41 @@ pub use crate::error::{OwEResult, OwError};
 pub struct Stream {
     stream: Option<TcpStream>,
     persist: bool,
-    target: String,
+    targets: Vec<String>,
+    current: usize,
+    timeout: Duration,
+    connect_timeout: Duration,
+    pool: Option<Arc<Mutex<Vec<TcpStream>>>>,
+    idle: bool,
+    reused: bool,
 }
 
 /// Clone Stream object
 /// Creates Stream with same persistance and target but closed connection
+/// * a connection pool (if any) is shared with the clone
 impl Clone for Stream {
     fn clone(&self) -> Self {
         Stream {
             stream: None,
             persist: self.persist,
-            target: self.target.clone(),
+            targets: self.targets.clone(),
+            current: self.current,
+            timeout: self.timeout,
+            connect_timeout: self.connect_timeout,
+            pool: self.pool.clone(),
+            idle: true,
+            reused: false,
         }
     }
 }
+/// Drop Stream object
+/// Best-effort shutdown of any open connection so owserver frees it promptly
+/// * returned to the pool instead if pooling
+impl Drop for Stream {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
 /// Default Stream
 impl Default for Stream {
     fn default() -> Self {
@@ -78,35 +100,141 @@ impl Stream {
         Stream {
             stream: None,
             persist: false,
-            target: "localhost:4304".to_string(),
+            targets: vec!["localhost:4304".to_string()],
+            current: 0,
+            timeout: Duration::from_secs(5),
+            connect_timeout: Duration::from_secs(5),
+            pool: None,
+            idle: true,
+            reused: false,
         }
     }
 
     /// ### set_timeout
-    /// Set a 5 second timeout for getting response
+    /// Set the timeout (default 5 seconds) for getting response
     /// * used for connections to an owserver
     /// * ping message should be received as a "keep alive" to show still thinking
     fn set_timeout(&self) -> OwEResult<()> {
         if let Some(stream) = &self.stream {
-            stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+            stream.set_read_timeout(Some(self.timeout))?;
         }
         Ok(())
     }
 
+    /// ### set_read_timeout
+    /// Set how long to wait for an owserver response
+    /// Applies to the current and future connections
+    pub fn set_read_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+        let _ = self.set_timeout();
+    }
+
+    /// ### set_connect_timeout
+    /// Set how long to wait for owserver to accept a connection (default 5 seconds)
+    /// * separate from the read timeout
+    /// * applies to each address of each target
+    pub fn set_connect_timeout(&mut self, timeout: Duration) {
+        self.connect_timeout = timeout;
+    }
+
+    // resolve a target ("host:port") to its socket addresses
+    fn resolve(target: &str) -> OwEResult<Vec<SocketAddr>> {
+        match target.to_socket_addrs() {
+            Ok(addrs) => {
+                let addrs: Vec<SocketAddr> = addrs.collect();
+                if addrs.is_empty() {
+                    Err(OwError::General(format!(
+                        "Cannot resolve owserver address {}",
+                        target
+                    )))
+                } else {
+                    Ok(addrs)
+                }
+            }
+            Err(e) => Err(OwError::General(format!(
+                "Cannot resolve owserver address {}: {}",
+                target, e
+            ))),
+        }
+    }
+
+    // connect to one target, trying each of its addresses
+    fn connect_target(&self, target: &str) -> OwEResult<TcpStream> {
+        let mut last_error = OwError::general("No owserver address");
+        for addr in Stream::resolve(target)? {
+            match TcpStream::connect_timeout(&addr, self.connect_timeout) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last_error = e.into(),
+            }
+        }
+        Err(last_error)
+    }
+
     /// ### connect
     /// Connect (via tcp network protocol) to a remote target
     /// * Tests if persistence is on
-    ///   * test if connection still works
-    /// * returns TcpStream errors or ()
+    ///   * reuses the connection if the last exchange finished and it still works
+    /// * Uses a pooled connection if available
+    /// * Tries each target in turn, starting with the last good one
+    ///   * waits at most the connect timeout for each address
+    /// * returns the last error (unresolvable name or TcpStream error) if none connect, or ()
     pub fn connect(&mut self) -> OwEResult<()> {
-        if self.stream.is_none() || !self.persist || !self.test() {
-            self.stream = None;
-            let stream = TcpStream::connect(&self.target)?;
+        if self.stream.is_some() && self.persist && self.idle && self.test() {
+            self.reused = true;
+            return Ok(());
+        }
+        self.discard();
+        if let Some(stream) = self.checkout() {
             self.stream = Some(stream);
-            self.set_timeout()
-        } else {
-            Ok(())
+            self.reused = true;
+            return self.set_timeout();
+        }
+        self.connect_new()
+    }
+
+    /// ### connect_new
+    /// Open a new connection (never the current or a pooled one)
+    /// * any current connection is shut down first
+    /// * returns the last error if no target connects, or ()
+    pub fn connect_new(&mut self) -> OwEResult<()> {
+        self.discard();
+        let n = self.targets.len();
+        let mut last_error = OwError::general("No owserver target");
+        for i in 0..n {
+            let index = (self.current + i) % n;
+            match self.connect_target(&self.targets[index]) {
+                Ok(stream) => {
+                    self.current = index;
+                    self.stream = Some(stream);
+                    self.reused = false;
+                    return self.set_timeout();
+                }
+                Err(e) => last_error = e,
+            }
         }
+        Err(last_error)
+    }
+
+    /// ### begin
+    /// A query is going out on the connection
+    /// * until **finish** the connection may hold unread replies
+    ///   * it won't be reused or pooled
+    pub fn begin(&mut self) {
+        self.idle = false;
+    }
+
+    /// ### finish
+    /// The whole reply (every packet) has been read
+    /// * the connection can be reused or pooled again
+    pub fn finish(&mut self) {
+        self.idle = true;
+    }
+
+    /// ### reused
+    /// was the current connection kept from an earlier exchange (or pooled)?
+    /// * a reused connection may have been closed by owserver meanwhile
+    pub fn reused(&self) -> bool {
+        self.reused
     }
 
     /// ### Set_persistence
@@ -118,11 +246,89 @@ impl Stream {
 
     /// ### Set_target
     /// Set target address and clear stream for safety
-    /// Does not alter persistence state
+    /// * a comma-separated list gives failover targets, tried in order
+    /// * Does not alter persistence state
+    /// * a pooled Stream gets a new (empty) pool of its own
     pub fn set_target(&mut self, target: &str) {
         //println!("Setting target: {}", target);
-        self.target = target.to_string();
+        self.targets = target
+            .split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(String::from)
+            .collect();
+        self.current = 0;
         self.stream = None;
+        if self.pool.is_some() {
+            self.pool = Some(Arc::new(Mutex::new(Vec::new())));
+        }
+    }
+
+    /// ### set_pool
+    /// Share open connections between this Stream and its clones
+    /// * a closed (or dropped) Stream returns its connection to the pool
+    ///   * only after a finished exchange (nothing left to read)
+    /// * **connect** takes a pooled connection before opening a new one
+    /// * turns on persistence (owserver must keep the connections open)
+    /// * `false` removes the pool (other clones keep theirs)
+    pub fn set_pool(&mut self, pool: bool) {
+        self.pool = match pool {
+            true => {
+                self.persist = true;
+                Some(Arc::new(Mutex::new(Vec::new())))
+            }
+            _ => None,
+        };
+    }
+
+    // take a working connection from the pool (if pooling)
+    // * dead connections are shut down and skipped
+    fn checkout(&mut self) -> Option<TcpStream> {
+        if !self.persist {
+            return None;
+        }
+        let mut pool = self.pool.as_ref()?.lock().ok()?;
+        while let Some(stream) = pool.pop() {
+            if Stream::usable(&stream) {
+                return Some(stream);
+            }
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+        None
+    }
+
+    /// ### target
+    /// owserver address in use
+    /// * the last one connected when there are several
+    pub fn target(&self) -> &str {
+        self.targets
+            .get(self.current)
+            .map(String::as_str)
+            .unwrap_or("")
+    }
+
+    /// ### targets
+    /// all owserver addresses, comma separated (as given to **set_target**)
+    pub fn targets(&self) -> String {
+        self.targets.join(",")
+    }
+
+    /// ### read_timeout
+    /// time allowed for an owserver response
+    pub fn read_timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// ### connect_timeout
+    /// time allowed for owserver to accept a connection
+    pub fn connect_timeout(&self) -> Duration {
+        self.connect_timeout
+    }
+
+    /// ### is_connected
+    /// is there an open connection?
+    pub fn is_connected(&self) -> bool {
+        self.stream.as_ref().is_some_and(|s| s.peer_addr().is_ok())
     }
 
     /// ### get
@@ -131,6 +337,43 @@ impl Stream {
         self.stream.as_mut()
     }
 
+    /// ### take
+    /// Remove the connection from the Stream and hand it to the caller
+    /// * Stream is left unconnected
+    /// * next **connect** makes a new connection
+    pub fn take(&mut self) -> Option<TcpStream> {
+        self.stream.take()
+    }
+
+    /// ### close
+    /// Shut down and drop the connection
+    /// * returned to the pool instead if pooling and the last exchange finished
+    /// * Stream is left unconnected
+    /// * next **connect** makes a new connection (or uses a pooled one)
+    pub fn close(&mut self) {
+        if let Some(stream) = self.stream.take() {
+            if self.persist && self.idle {
+                if let Some(Ok(mut pool)) = self.pool.as_ref().map(|p| p.lock()) {
+                    pool.push(stream);
+                    return;
+                }
+            }
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+        self.idle = true;
+    }
+
+    /// ### discard
+    /// Shut down and drop the connection (never pooled)
+    /// * after an error, timeout or abandoned reply
+    /// * Stream is left unconnected
+    pub fn discard(&mut self) {
+        if let Some(stream) = self.stream.take() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+        self.idle = true;
+    }
+
     /// ### get_persistence
     /// get persistence state for marking message flag
     pub fn get_persistence(&self) -> bool {
@@ -139,9 +382,170 @@ impl Stream {
 
     // test the connection (for persistent connctions to see if still valid)
     fn test(&mut self) -> bool {
-        match self.stream.as_mut() {
-            Some(s) => matches!(s.write_all(&[]), Ok(())), // test existing connection
-            _ => false,
+        self.stream.as_ref().is_some_and(Stream::usable)
+    }
+
+    // an idle connection is usable if nothing is waiting to be read
+    // * waiting data is a stray reply, end of file is owserver hanging up
+    fn usable(stream: &TcpStream) -> bool {
+        if stream.set_nonblocking(true).is_err() {
+            return false;
         }
+        let waiting = stream.peek(&mut [0u8; 1]);
+        let usable = matches!(waiting, Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock);
+        stream.set_nonblocking(false).is_ok() && usable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    // address of a port that refuses connections
+    fn closed_port() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().to_string()
+    }
+
+    #[test]
+    fn target_list_test() {
+        let mut stream = Stream::new();
+        stream.set_target("10.0.0.1:4304, 10.0.0.2:4304");
+        assert_eq!(stream.targets, vec!["10.0.0.1:4304", "10.0.0.2:4304"]);
+        assert_eq!(stream.target(), "10.0.0.1:4304");
+    }
+
+    #[test]
+    fn failover_test() {
+        let closed = closed_port();
+        let live = TcpListener::bind("127.0.0.1:0").unwrap();
+        let live_address = live.local_addr().unwrap().to_string();
+
+        let mut stream = Stream::new();
+        stream.set_target(&format!("{},{}", closed, live_address));
+        stream.connect().unwrap();
+        assert!(stream.get().is_some());
+        assert_eq!(stream.target(), live_address);
+
+        // last good target is tried first next time
+        stream.connect().unwrap();
+        assert_eq!(stream.current, 1);
+    }
+
+    #[test]
+    fn close_test() {
+        let live = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut stream = Stream::new();
+        stream.set_target(&live.local_addr().unwrap().to_string());
+        stream.connect().unwrap();
+        stream.close();
+        assert!(stream.get().is_none());
+        stream.close(); // harmless when already closed
+
+        // server sees the connection end
+        let (mut server, _) = live.accept().unwrap();
+        let mut buf = [0u8; 1];
+        assert_eq!(std::io::Read::read(&mut server, &mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn timeout_setting_test() {
+        let mut stream = Stream::new();
+        stream.set_connect_timeout(Duration::from_millis(200));
+        assert_eq!(stream.connect_timeout(), Duration::from_millis(200));
+        assert_eq!(stream.read_timeout(), Duration::from_secs(5));
+    }
+
+    #[test]
+    #[ignore = "needs a network that drops packets to 10.255.255.1"]
+    fn connect_timeout_test() {
+        // non-routable address: connection attempt hangs without a timeout
+        let mut stream = Stream::new();
+        stream.set_target("10.255.255.1:4304");
+        stream.set_connect_timeout(Duration::from_millis(200));
+        let start = std::time::Instant::now();
+        assert!(stream.connect().is_err());
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn resolve_error_test() {
+        let mut stream = Stream::new();
+        stream.set_target("no-such-owserver.invalid:4304");
+        let e = stream.connect().unwrap_err();
+        assert!(e.to_string().contains("Cannot resolve owserver address"));
+    }
+
+    #[test]
+    fn pool_test() {
+        let live = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut first = Stream::new();
+        first.set_target(&live.local_addr().unwrap().to_string());
+        first.set_pool(true);
+        assert!(first.get_persistence());
+        let mut second = first.clone();
+
+        first.connect().unwrap();
+        let port = first.get().unwrap().local_addr().unwrap();
+        drop(first); // back to the pool
+
+        second.connect().unwrap();
+        assert_eq!(second.get().unwrap().local_addr().unwrap(), port);
+
+        // only one connection was ever made
+        live.accept().unwrap();
+        live.set_nonblocking(true).unwrap();
+        assert!(live.accept().is_err());
+    }
+
+    #[test]
+    fn unfinished_pool_test() {
+        let live = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut first = Stream::new();
+        first.set_target(&live.local_addr().unwrap().to_string());
+        first.set_pool(true);
+        let mut second = first.clone();
+
+        // query sent, reply never read: not pooled
+        first.connect().unwrap();
+        first.begin();
+        drop(first);
+        second.connect().unwrap();
+        assert!(!second.reused());
+
+        // finished exchange is pooled
+        second.finish();
+        second.close();
+        second.connect().unwrap();
+        assert!(second.reused());
+    }
+
+    #[test]
+    fn stray_reply_test() {
+        let live = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut stream = Stream::new();
+        stream.set_target(&live.local_addr().unwrap().to_string());
+        stream.set_pool(true);
+        stream.connect().unwrap();
+        let port = stream.get().unwrap().local_addr().unwrap();
+        stream.close(); // pooled
+
+        // leftover bytes waiting on the pooled connection
+        let (mut server, _) = live.accept().unwrap();
+        std::io::Write::write_all(&mut server, b"stale").unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+
+        stream.connect().unwrap();
+        assert!(!stream.reused());
+        assert_ne!(stream.get().unwrap().local_addr().unwrap(), port);
+    }
+
+    #[test]
+    fn all_fail_test() {
+        let mut stream = Stream::new();
+        stream.set_target(&format!("{},{}", closed_port(), closed_port()));
+        assert!(matches!(stream.connect(), Err(OwError::Io(_))));
+        assert!(stream.get().is_none());
     }
 }
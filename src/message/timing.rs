@@ -0,0 +1,84 @@
+//! **owrust** Rust library interfaces with owserver to use 1-wire devices
+//!
+//! This is a tool in the 1-wire file system **OWFS**
+//!
+//! This library is the central part of **owrust** -- the _rust language_ OWFS programs
+//! * **OWFS** [documentation](https://owfs.org) and [code](https://github.com/owfs/owfs)
+//! * **owrust** [repository](https://github.com/alfille/owrust)
+//!
+//! ## PURPOSE
+//! Timing holds the duration of each owserver protocol phase
+//! * filled in by every operation
+//! * printed to stderr with the _--profile_ option
+
+// owrust project
+// https://github.com/alfille/owrust
+//
+// This is a Rust version of my C owfs code for talking to 1-wire devices via owserver
+// Basically owserver can talk to the physical devices, and provides network access via my "owserver protocol"
+//
+// MIT Licence
+// {c} 2025 Paul H Alfille
+
+use std::time::Duration;
+
+/// ### Timing
+/// Time spent in each protocol phase of the last owserver operation
+/// * connect -- establishing (or checking) the tcp connection
+/// * send -- writing the query
+/// * receive -- waiting for and reading all response packets
+///
+/// Separates network latency from owserver processing time.
+/// Shown on stderr with the _--profile_ option
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Timing {
+    pub connect: Option<Duration>,
+    pub send: Option<Duration>,
+    pub receive: Option<Duration>,
+}
+
+impl Timing {
+    /// ### add_receive
+    /// accumulate receive time (directories can take several packets)
+    pub fn add_receive(&mut self, time: Duration) {
+        self.receive = Some(self.receive.unwrap_or_default() + time);
+    }
+
+    /// ### summary
+    /// 1-line breakdown of the phases
+    pub fn summary(&self) -> String {
+        format!(
+            "Profile: connect {} send {} receive {}",
+            Timing::show(self.connect),
+            Timing::show(self.send),
+            Timing::show(self.receive),
+        )
+    }
+
+    fn show(phase: Option<Duration>) -> String {
+        match phase {
+            Some(d) => format!("{:.3}ms", d.as_secs_f64() * 1000.0),
+            None => "-".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn summary_test() {
+        let mut timing = Timing {
+            connect: Some(Duration::from_micros(1500)),
+            send: None,
+            receive: None,
+        };
+        timing.add_receive(Duration::from_millis(2));
+        timing.add_receive(Duration::from_millis(3));
+        assert_eq!(timing.receive, Some(Duration::from_millis(5)));
+        assert_eq!(
+            timing.summary(),
+            "Profile: connect 1.500ms send - receive 5.000ms"
+        );
+    }
+}
@@ -0,0 +1,372 @@
+//! **owrust** Rust library interfaces with owserver to use 1-wire devices
+//!
+//! This is a tool in the 1-wire file system **OWFS**
+//!
+//! This library is the central part of **owrust** -- the _rust language_ OWFS programs
+//! * **OWFS** [documentation](https://owfs.org) and [code](https://github.com/owfs/owfs)
+//! * **owrust** [repository](https://github.com/alfille/owrust)
+//!
+//! ## PURPOSE
+//! TreeWalker visits every entry below a 1-wire path
+//! * depth-first, in directory order (the order **owtree** shows)
+//! * an `Iterator` of (depth, DirEntry) so programs can render or search the tree themselves
+//! * directories already visited are not entered again
+//! * depth is bounded
+//! * stops early if the OwMessage stop flag is set (**set_cancel**)
+//!
+//! ## EXAMPLES
+//! ```no_run
+//! use owrust::tree::TreeWalker;
+//!
+//! let mut owserver = owrust::new();
+//! for (depth, entry) in TreeWalker::new(&mut owserver, "/") {
+//!     println!("{}{}", "  ".repeat(depth), entry.name);
+//! }
+//! ```
+
+// owrust project
+// https://github.com/alfille/owrust
+//
+// This is a Rust version of my C owfs code for talking to 1-wire devices via owserver
+// Basically owserver can talk to the physical devices, and provides network access via my "owserver protocol"
+//
+// MIT Licence
+// {c} 2025 Paul H Alfille
+
+use std::collections::{HashSet, VecDeque};
+
+use crate::rom_id::RomId;
+use crate::OwMessage;
+
+// Convenience device entries (repeats of the address) left out by _--prune_
+pub(crate) const PRUNE_LIST: &[&str] = &[
+    "address",
+    "crc8",
+    "family",
+    "id",
+    "locator",
+    "r_address",
+    "r_id",
+    "r_locator",
+    "type",
+    "bus",
+];
+
+// owserver's own directories (not 1-wire devices)
+const SYSTEM_LIST: &[&str] = &[
+    "alarm",
+    "bus",
+    "settings",
+    "simultaneous",
+    "statistics",
+    "structure",
+    "system",
+    "uncached",
+];
+
+/// ### EntryKind
+/// What a directory entry is, judged from its name
+/// * Device -- a valid 1-wire address (e.g. `10.67C6697351FF`, see **RomId::parse**)
+///   * the check byte (crc8) is tested when given
+/// * Virtual -- owserver's own directories (`bus.0`, `settings`, ...) and convenience entries (`address`, `crc8`, ...)
+/// * Directory -- any other directory (e.g. `errata`)
+/// * Property -- any other file (e.g. `temperature`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    Device,
+    Directory,
+    Property,
+    Virtual,
+}
+
+impl EntryKind {
+    /// ### of
+    /// Kind of the entry at path (only the last element counts)
+    /// * dir -- the entry is a directory
+    pub fn of(path: &str, dir: bool) -> EntryKind {
+        let name = OwMessage::leaf_name(path);
+        if name.parse::<RomId>().is_ok() {
+            return EntryKind::Device;
+        }
+        let base = OwMessage::basename(name);
+        if SYSTEM_LIST.contains(&base.as_str()) || PRUNE_LIST.contains(&base.as_str()) {
+            EntryKind::Virtual
+        } else if dir {
+            EntryKind::Directory
+        } else {
+            EntryKind::Property
+        }
+    }
+}
+
+/// ### DirEntry
+/// One entry of the tree
+/// * path -- full 1-wire path (directories end with `/`)
+/// * name -- last element of the path, for display
+/// * dir -- is a directory (contents follow)
+/// * last -- last entry of its directory (for drawing tree lines)
+/// * kind -- device, directory, property or virtual (see **EntryKind**)
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirEntry {
+    pub path: String,
+    pub name: String,
+    pub dir: bool,
+    pub last: bool,
+    pub kind: EntryKind,
+}
+
+impl DirEntry {
+    // entry from a dirallslash listing
+    fn new(path: String, last: bool) -> Self {
+        let name = OwMessage::leaf_name(&path).to_string();
+        let dir = path.ends_with('/');
+        let kind = EntryKind::of(&path, dir);
+        DirEntry {
+            path,
+            name,
+            dir,
+            last,
+            kind,
+        }
+    }
+}
+
+/// ### TreeWalker
+/// Iterator over the tree below a path
+/// * first item is the path itself (depth 0), then its contents (depth 1) and so on
+/// * directories that can't be read are skipped (see **take_errors**)
+pub struct TreeWalker<'a> {
+    owserver: &'a mut OwMessage,
+    root: Option<DirEntry>,
+    stack: Vec<(usize, VecDeque<DirEntry>)>,
+    visited: HashSet<String>,
+    max_depth: usize,
+    errors: Vec<String>,
+}
+
+impl<'a> TreeWalker<'a> {
+    // Far deeper than any real owserver tree
+    const MAX_DEPTH: usize = 32;
+
+    /// ### new
+    /// Walk the tree below path (e.g. `/`)
+    pub fn new(owserver: &'a mut OwMessage, path: &str) -> Self {
+        TreeWalker {
+            owserver,
+            root: Some(DirEntry {
+                path: path.to_string(),
+                name: path.to_string(),
+                dir: true,
+                last: true,
+                kind: EntryKind::of(path, true),
+            }),
+            stack: Vec::new(),
+            visited: HashSet::new(),
+            max_depth: TreeWalker::MAX_DEPTH,
+            errors: Vec::new(),
+        }
+    }
+
+    /// ### max_depth
+    /// Deepest level to list (default 32)
+    /// * directories at this depth are shown but not entered
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// ### take_errors
+    /// Problems met so far (unreadable, repeated or too-deep directories)
+    /// * list is emptied
+    pub fn take_errors(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.errors)
+    }
+
+    // list a directory's contents to be walked next
+    fn descend(&mut self, entry: &DirEntry, depth: usize) {
+        if depth >= self.max_depth {
+            self.errors.push(format!(
+                "{} deeper than {} levels",
+                entry.path, self.max_depth
+            ));
+            return;
+        }
+        let key = format!("{}/", entry.path.trim_end_matches('/'));
+        if !self.visited.insert(key) {
+            self.errors
+                .push(format!("{} already listed (cycle)", entry.path));
+            return;
+        }
+        match self.owserver.dirallslash(&entry.path) {
+            Ok(d) => {
+                let paths: Vec<String> = d.into_iter().filter(|p| !p.is_empty()).collect();
+                let n = paths.len();
+                let entries = paths
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, p)| DirEntry::new(p, i + 1 == n))
+                    .collect();
+                self.stack.push((depth + 1, entries));
+            }
+            Err(e) => {
+                self.errors
+                    .push(format!("Trouble reading directory {}: {}", entry.path, e));
+            }
+        }
+    }
+}
+
+impl Iterator for TreeWalker<'_> {
+    type Item = (usize, DirEntry);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // stop flag (see **OwMessage::set_cancel**)
+        if self.owserver.cancelled() {
+            if self.root.take().is_some() || !self.stack.is_empty() {
+                self.stack.clear();
+                self.errors.push("cancelled".to_string());
+            }
+            return None;
+        }
+        if let Some(root) = self.root.take() {
+            self.descend(&root, 0);
+            return Some((0, root));
+        }
+        loop {
+            let (depth, entries) = self.stack.last_mut()?;
+            let depth = *depth;
+            match entries.pop_front() {
+                Some(entry) => {
+                    if entry.dir {
+                        self.descend(&entry, depth);
+                    }
+                    return Some((depth, entry));
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::query::OwQuery;
+    use crate::message::tests::{mock_client, mock_owserver, query_path, reply};
+
+    fn mock_tree() -> String {
+        mock_owserver(|q| match (q.mtype, query_path(q).as_str()) {
+            (OwQuery::DIRALLSLASH, "/") => {
+                vec![reply(0, b"/10.67C6697351FF/,/05.4AEC29CDBAAB/")]
+            }
+            (OwQuery::DIRALLSLASH, "/10.67C6697351FF/") => vec![reply(
+                0,
+                b"/10.67C6697351FF/errata/,/10.67C6697351FF/temperature",
+            )],
+            (OwQuery::DIRALLSLASH, "/10.67C6697351FF/errata/") => {
+                vec![reply(0, b"/10.67C6697351FF/errata/die")]
+            }
+            (OwQuery::DIRALLSLASH, "/05.4AEC29CDBAAB/") => {
+                // misbehaving server: lists itself
+                vec![reply(0, b"/05.4AEC29CDBAAB/PIO,/05.4AEC29CDBAAB/")]
+            }
+            _ => vec![reply(-2, b"")],
+        })
+    }
+
+    #[test]
+    fn walk_test() {
+        let mut owc = mock_client(&mock_tree());
+        let mut walker = TreeWalker::new(&mut owc, "/");
+        let items: Vec<(usize, String, bool)> = walker
+            .by_ref()
+            .map(|(depth, e)| (depth, e.name, e.last))
+            .collect();
+        let expected = [
+            (0, "/", true),
+            (1, "10.67C6697351FF", false),
+            (2, "errata", false),
+            (3, "die", true),
+            (2, "temperature", true),
+            (1, "05.4AEC29CDBAAB", true),
+            (2, "PIO", false),
+            (2, "05.4AEC29CDBAAB", true),
+        ]
+        .map(|(d, n, l)| (d, n.to_string(), l));
+        assert_eq!(items, expected.to_vec());
+
+        // the repeated directory was not entered again
+        let errors = walker.take_errors();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("cycle"));
+    }
+
+    #[test]
+    fn cancel_test() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+        let mut owc = mock_client(&mock_tree());
+        let stop = Arc::new(AtomicBool::new(false));
+        owc.set_cancel(stop.clone());
+        let mut walker = TreeWalker::new(&mut owc, "/");
+        assert_eq!(walker.next().unwrap().1.name, "/");
+        stop.store(true, Ordering::Relaxed);
+        assert!(walker.next().is_none());
+        assert!(walker.next().is_none());
+        assert_eq!(walker.take_errors(), vec!["cancelled".to_string()]);
+    }
+
+    #[test]
+    fn kind_test() {
+        let entries = [
+            ("/10.67C6697351FF/", EntryKind::Device),
+            ("/1067C6697351FF", EntryKind::Device),
+            ("/28.0000000000AB.91", EntryKind::Device),
+            ("/28.0000000000AB.CD", EntryKind::Property),
+            ("/1.067C6697351FF", EntryKind::Property),
+            ("/bus.0/", EntryKind::Virtual),
+            ("/settings/", EntryKind::Virtual),
+            ("/statistics/", EntryKind::Virtual),
+            ("/uncached/", EntryKind::Virtual),
+            ("/10.67C6697351FF/address", EntryKind::Virtual),
+            ("/10.67C6697351FF/crc8", EntryKind::Virtual),
+            ("/10.67C6697351FF/errata/", EntryKind::Directory),
+            ("/10.67C6697351FF/temperature", EntryKind::Property),
+            ("/10.67C6697351FF/TAI8570/", EntryKind::Directory),
+            ("/29.0123456789AB/PIO.A", EntryKind::Property),
+        ];
+        for (path, kind) in entries {
+            assert_eq!(
+                DirEntry::new(path.to_string(), false).kind,
+                kind,
+                "{}",
+                path
+            );
+        }
+        // root of the walk
+        let mut owc = mock_client(&mock_tree());
+        let kinds: Vec<EntryKind> = TreeWalker::new(&mut owc, "/")
+            .map(|(_, e)| e.kind)
+            .collect();
+        assert_eq!(
+            kinds[..3],
+            [
+                EntryKind::Directory,
+                EntryKind::Device,
+                EntryKind::Directory
+            ]
+        );
+    }
+
+    #[test]
+    fn depth_test() {
+        let mut owc = mock_client(&mock_tree());
+        let names: Vec<String> = TreeWalker::new(&mut owc, "/")
+            .max_depth(1)
+            .map(|(_, e)| e.name)
+            .collect();
+        assert_eq!(names, vec!["/", "10.67C6697351FF", "05.4AEC29CDBAAB"]);
+    }
+}
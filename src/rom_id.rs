@@ -17,6 +17,13 @@
 //! let c:u8 = crc8( &[0x10, 0x67, 0xc6, 0x69, 0x73, 0x51, 0xff, 0x8d] ) ;
 //! assert_eq!(c,0x00);
 //! ```
+//! Example for changing the written form of an address
+//! ```
+//! use owrust::message::Format;
+//! use owrust::rom_id::reformat_address;
+//! let a = reformat_address( "/10.67C6697351FF", Format::FIC ).unwrap();
+//! assert_eq!(a,"/1067C6697351FF8D");
+//! ```
 //!
 
 // owrust project
@@ -30,10 +37,26 @@
 
 use std::convert::TryInto;
 use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
 
-#[derive(Debug, Clone, Copy)]
+use crate::error::{OwEResult, OwError};
+use crate::message::Format;
+use crate::OwMessage;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct RomId([u8; 8]);
 
+impl From<[u8; 8]> for RomId {
+    fn from(rom: [u8; 8]) -> Self {
+        Self(rom)
+    }
+}
+impl AsRef<[u8]> for RomId {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
 impl Deref for RomId {
     type Target = [u8; 8];
 
@@ -96,6 +119,72 @@ impl RomId {
     pub fn make_crc8(&self) -> u8 {
         crc8(&self[0..7])
     }
+    /// ### parse
+    /// ROM id from a written address
+    /// * any format: `10.67C6697351FF`, `1067C6697351FF8D`, `10.67C6697351FF.8D`, ...
+    /// * surrounding `/` ignored (a path to the device)
+    /// * dots only after the family code and before the crc8
+    /// * crc8 generated if not given, checked if given
+    pub fn parse(addr: &str) -> OwEResult<Self> {
+        let name = addr.trim_matches('/');
+        let parts: Vec<usize> = name.split('.').map(str::len).collect();
+        let layout = matches!(
+            parts.as_slice(),
+            [14] | [16] | [2, 12] | [14, 2] | [2, 14] | [2, 12, 2]
+        );
+        if !layout || !OwMessage::is_address(name) {
+            return Err(OwError::input(format!("Not a 1-wire address: {}", addr)));
+        }
+        let hex: String = name.chars().filter(|&c| c != '.').collect();
+        let bytes: Vec<u8> = (0..hex.len())
+            .step_by(2)
+            .filter_map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+            .collect();
+        let rom = RomId::new(bytes);
+        if !rom.test_crc8() {
+            return Err(OwError::input(format!("Bad checksum in address: {}", addr)));
+        }
+        Ok(rom)
+    }
+    /// ### to_format
+    /// written address in the given Format (upper case hex)
+    /// * e.g. `10.67C6697351FF` for Format::FdI (also the DEFAULT)
+    pub fn to_format(&self, format: &Format) -> String {
+        let f = format!("{:02X}", self.family());
+        let i: String = self.id().iter().map(|b| format!("{:02X}", b)).collect();
+        let c = format!("{:02X}", self.crc8());
+        match format {
+            Format::FI => format!("{}{}", f, i),
+            Format::FdI | Format::DEFAULT => format!("{}.{}", f, i),
+            Format::FIC => format!("{}{}{}", f, i, c),
+            Format::FIdC => format!("{}{}.{}", f, i, c),
+            Format::FdIC => format!("{}.{}{}", f, i, c),
+            Format::FdIdC => format!("{}.{}.{}", f, i, c),
+        }
+    }
+}
+
+/// ### RomId from a written address
+/// * same as **RomId::parse**
+impl FromStr for RomId {
+    type Err = OwError;
+    fn from_str(addr: &str) -> OwEResult<Self> {
+        RomId::parse(addr)
+    }
+}
+
+/// ### reformat_address
+/// write a 1-wire address in another format
+/// * e.g. `10.67C6697351FF` => `1067C6697351FF8D` (Format::FIC)
+/// * a leading `/` (device path) is kept
+/// * the crc8 is added or dropped as the format requires
+pub fn reformat_address(addr: &str, target: Format) -> OwEResult<String> {
+    let rom = RomId::parse(addr)?;
+    let root = match addr.starts_with('/') {
+        true => "/",
+        _ => "",
+    };
+    Ok(format!("{}{}", root, rom.to_format(&target)))
 }
 
 /* bit-wise
@@ -152,6 +241,22 @@ pub fn crc8_seeded(bytes: &[u8], seed: u8) -> u8 {
 mod tests {
     use super::*;
     #[test]
+    /// usable as a map key
+    fn t_rom_key() {
+        use std::collections::HashMap;
+        let data = [0x10, 0x67, 0xc6, 0x69, 0x73, 0x51, 0xff, 0x8d];
+        let rom = RomId::from(data);
+        assert_eq!(rom, RomId::new(data));
+        assert_ne!(rom, RomId::blank());
+        assert_eq!(rom.as_ref(), &data[..]);
+
+        let mut readings: HashMap<RomId, f64> = HashMap::new();
+        readings.insert(rom, 85.75);
+        readings.insert(RomId::new(&data[..7]), 70.7); // same ROM, crc8 generated
+        assert_eq!(readings.len(), 1);
+        assert_eq!(readings[&RomId::from(data)], 70.7);
+    }
+    #[test]
     /// Ignores last byte
     fn t_rom9() {
         let data = [0x10, 0x67, 0xc6, 0x69, 0x73, 0x51, 0xff, 0x8d, 0xFF];
@@ -208,4 +313,30 @@ mod tests {
         assert_eq!(rom.make_crc8(), 0x00);
         assert_eq!(rom.id(), [0u8; 6]);
     }
+    #[test]
+    /// one address through every format and back
+    fn t_reformat() {
+        let formats = [
+            (Format::FI, "1067C6697351FF"),
+            (Format::FdI, "10.67C6697351FF"),
+            (Format::FIC, "1067C6697351FF8D"),
+            (Format::FIdC, "1067C6697351FF.8D"),
+            (Format::FdIC, "10.67C6697351FF8D"),
+            (Format::FdIdC, "10.67C6697351FF.8D"),
+        ];
+        for (format, written) in formats.iter() {
+            assert_eq!(
+                reformat_address("10.67c6697351ff", format.clone()).unwrap(),
+                *written
+            );
+            assert_eq!(
+                reformat_address(&format!("/{}", written), Format::DEFAULT).unwrap(),
+                "/10.67C6697351FF"
+            );
+        }
+        assert!(reformat_address("10.67C6697351FF.8E", Format::FI).is_err());
+        assert!(reformat_address("10.67C6697351", Format::FI).is_err());
+        assert!(reformat_address("10.67C669735XFF", Format::FI).is_err());
+        assert!(reformat_address("1.067C6697351FF", Format::FI).is_err());
+    }
 }